@@ -0,0 +1,95 @@
+//! Throughput of the board evaluation and of the search over a fixed set of
+//! positions, run with `cargo bench --bench search [-- max_depth]`.
+
+use game_play::analysis::position::parse_moves;
+use game_play::api::game_connection::NoConnection;
+use game_play::five_in_a_row::FiveInRow;
+use game_play::game::Game;
+use game_play::gameplay::GamePlay;
+use std::collections::VecDeque;
+use std::env;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const DEFAULT_MAX_DEPTH: u8 = 4;
+/// Minimal time spent measuring the board functions
+const MEASURE_TIME: Duration = Duration::from_millis(500);
+
+/// Positions with us on the move, from the opening to a crowded board
+const POSITIONS: [(&str, &str); 4] = [
+    ("opening", "h8 i9 g7 h9"),
+    ("early", "h8 h9 i8 h10 j8 h11 g8 f8 i9 j10"),
+    (
+        "middle",
+        "h8 g8 d8 f9 h7 h9 h6 g10 h5 h4 g9 f11 i8 e10 f10 i7 e11 d12 e12 f7 e6 e8",
+    ),
+    (
+        "crowded",
+        "h8 g7 g8 f8 e9 f7 e7 f9 f6 f10 f11 g10 e8 e10 h10 d10 c10 e5 d8 g5 c9 b10 c11 c12 b13 c8",
+    ),
+];
+
+/// Calls the function repeatedly for at least `MEASURE_TIME`, returns the
+/// mean time per call and the number of calls per second
+fn measure<F: FnMut()>(mut f: F) -> (Duration, f64) {
+    let started = Instant::now();
+    let mut calls = 0;
+    while started.elapsed() < MEASURE_TIME {
+        for _ in 0..100 {
+            f();
+        }
+        calls += 100;
+    }
+    let elapsed = started.elapsed();
+    (elapsed / calls, per_second(u64::from(calls), elapsed))
+}
+
+fn per_second(count: u64, duration: Duration) -> f64 {
+    count as f64 / duration.as_secs_f64()
+}
+
+fn main() {
+    let max_depth = env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_MAX_DEPTH);
+
+    for (name, moves) in POSITIONS.iter() {
+        let moves = parse_moves(moves, moves.split_whitespace().count() % 2 == 0).unwrap();
+        let game = FiveInRow::from_moves(moves);
+        println!("{} ({} stones)", name, game.moves.len());
+
+        let (mean, rate) = measure(|| {
+            black_box(black_box(&game).get_score());
+        });
+        println!(
+            "  get_score           {:>10.2?}/call  {:>12.0} calls/s",
+            mean, rate
+        );
+        let (mean, rate) = measure(|| {
+            black_box(black_box(&game).get_possible_moves(true));
+        });
+        println!(
+            "  get_possible_moves  {:>10.2?}/call  {:>12.0} calls/s  {} moves",
+            mean,
+            rate,
+            game.get_possible_moves(true).len()
+        );
+
+        for depth in 0..=max_depth {
+            let mut game_play = GamePlay::<FiveInRow, NoConnection>::from_game(game.clone());
+            let started = Instant::now();
+            game_play
+                .compute_suggestions(true, VecDeque::new(), depth)
+                .unwrap();
+            let elapsed = started.elapsed();
+            println!(
+                "  depth {}             {:>10.2?}        {:>12.0} nodes/s  {} nodes",
+                depth,
+                elapsed,
+                per_second(game_play.nodes, elapsed),
+                game_play.nodes
+            );
+        }
+    }
+}
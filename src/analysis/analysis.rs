@@ -0,0 +1,195 @@
+#[path = "blunders.rs"]
+pub mod blunders;
+#[path = "opening.rs"]
+pub mod opening;
+#[path = "position.rs"]
+pub mod position;
+#[path = "puzzles.rs"]
+pub mod puzzles;
+#[path = "replay.rs"]
+pub mod replay;
+#[path = "stats.rs"]
+pub mod stats;
+#[path = "tuning.rs"]
+pub mod tuning;
+
+use crate::api::game_connection::NoConnection;
+use crate::five_in_a_row::{diagram, mv::FiveInRowMove, psq, FiveInRow};
+use crate::game::record::{self, GameRecord};
+use crate::game::{error::Error as GameError, GameMove};
+use crate::gameplay::{suggestion::Suggestion, GamePlay};
+use std::collections::VecDeque;
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum Error {
+    Record(record::Error),
+    Psq(psq::Error),
+    Diagram(diagram::Error),
+    Game(GameError<FiveInRow>),
+    UnknownFormat(PathBuf),
+    Parse(String),
+}
+impl From<record::Error> for Error {
+    fn from(e: record::Error) -> Self {
+        Self::Record(e)
+    }
+}
+impl From<psq::Error> for Error {
+    fn from(e: psq::Error) -> Self {
+        Self::Psq(e)
+    }
+}
+impl From<diagram::Error> for Error {
+    fn from(e: diagram::Error) -> Self {
+        Self::Diagram(e)
+    }
+}
+impl From<GameError<FiveInRow>> for Error {
+    fn from(e: GameError<FiveInRow>) -> Self {
+        Self::Game(e)
+    }
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Record(e) => write!(f, "Unable to read the game record ({})", e),
+            Self::Psq(e) => write!(f, "Unable to read the psq file ({})", e),
+            Self::Diagram(e) => write!(f, "Invalid diagram ({})", e),
+            Self::Game(e) => write!(f, "Game error ({})", e),
+            Self::UnknownFormat(path) => write!(f, "Unknown file format ({})", path.display()),
+            Self::Parse(input) => write!(f, "Unable to parse the input ({})", input),
+        }
+    }
+}
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Record(err) => Some(err),
+            Self::Psq(err) => Some(err),
+            Self::Diagram(err) => Some(err),
+            Self::Game(err) => Some(err),
+            Self::UnknownFormat(_) => None,
+            Self::Parse(_) => None,
+        }
+    }
+}
+
+/// Game loaded from a game record (`.json`) or a Piskvork file (`.psq`)
+#[derive(Debug, Clone)]
+pub struct LoadedGame {
+    pub moves: Vec<FiveInRowMove>,
+    pub record: Option<GameRecord<FiveInRowMove>>,
+}
+
+impl LoadedGame {
+    pub fn from_moves(moves: Vec<FiveInRowMove>) -> Self {
+        Self {
+            moves,
+            record: None,
+        }
+    }
+
+    /// Whether we won the game, `None` when the result is unknown
+    pub fn is_won(&self) -> Option<bool> {
+        match self.record.as_ref() {
+            Some(record) => record.is_won(),
+            None => FiveInRow::from_moves(self.moves.clone())
+                .get_winning_line()
+                .map(|line| line[0].is_mine()),
+        }
+    }
+
+    /// Position after the given number of moves
+    pub fn get_position(&self, ply: usize) -> FiveInRow {
+        FiveInRow::from_moves(self.moves[0..ply.min(self.moves.len())].to_vec())
+    }
+}
+
+/// Loads the game, moves of the first player in psq files are considered ours
+pub fn load_game(path: &Path) -> Result<LoadedGame, Error> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => {
+            let record = GameRecord::<FiveInRowMove>::load(path)?;
+            Ok(LoadedGame {
+                moves: record.get_moves(),
+                record: Some(record),
+            })
+        }
+        Some("psq") => Ok(LoadedGame::from_moves(psq::load(path, true)?.moves)),
+        _ => Err(Error::UnknownFormat(path.to_path_buf())),
+    }
+}
+
+/// Expands the directories to the game records and psq files they contain
+pub fn find_game_files(paths: &[PathBuf]) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths.iter() {
+        if path.is_dir() {
+            let mut entries = fs::read_dir(path)?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| {
+                    p.extension()
+                        .is_some_and(|ext| ext == "json" || ext == "psq")
+                })
+                .collect::<Vec<_>>();
+            entries.sort();
+            files.extend(entries);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+/// Whether we are the one to play the next move in the position
+pub fn is_my_turn(game: &FiveInRow) -> bool {
+    game.moves.last().is_none_or(|mv| !mv.is_mine())
+}
+
+/// Runs the engine search and returns the suggestions sorted from the best one
+pub fn evaluate_for(
+    game: &FiveInRow,
+    myself: bool,
+    depth: u8,
+) -> Result<Vec<Suggestion<FiveInRow>>, Error> {
+    let mut game_play = GamePlay::<FiveInRow, NoConnection>::from_game(game.clone());
+    game_play.compute_suggestions(myself, VecDeque::new(), depth)?;
+    Ok(game_play.suggestions)
+}
+
+/// Runs the engine search and returns the best suggestion for the side to move
+pub fn evaluate(game: &FiveInRow, depth: u8) -> Result<Option<Suggestion<FiveInRow>>, Error> {
+    Ok(evaluate_for(game, is_my_turn(game), depth)?
+        .into_iter()
+        .next())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_evaluates_position() {
+        let game = FiveInRow::from_moves(vec![
+            FiveInRowMove::Mine(0, 0),
+            FiveInRowMove::Rivals(1, 0),
+            FiveInRowMove::Mine(0, 1),
+            FiveInRowMove::Rivals(1, 1),
+            FiveInRowMove::Mine(0, 2),
+            FiveInRowMove::Rivals(1, 2),
+            FiveInRowMove::Mine(0, 3),
+            FiveInRowMove::Rivals(1, 3),
+        ]);
+        assert!(is_my_turn(&game));
+        let best = evaluate(&game, 0).unwrap().unwrap();
+        assert!(
+            *best.get_move() == FiveInRowMove::Mine(0, 4)
+                || *best.get_move() == FiveInRowMove::Mine(0, -1)
+        );
+        assert_eq!(best.get_deep_score(), crate::game::score::Score::Win);
+    }
+}
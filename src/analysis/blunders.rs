@@ -0,0 +1,129 @@
+use crate::analysis::{evaluate, Error, LoadedGame};
+use crate::five_in_a_row::mv::FiveInRowMove;
+use crate::five_in_a_row::render;
+use crate::game::{score::Score, GameMove};
+use std::fmt::Write;
+
+#[derive(Debug, Clone)]
+pub struct MoveAnalysis {
+    /// Number of moves played before the analysed move
+    pub ply: usize,
+    pub played: FiveInRowMove,
+    /// Deep score of the best move available in the position
+    pub score_before: Score,
+    /// Deep score of the position after the played move
+    pub score_after: Score,
+    pub best: FiveInRowMove,
+    pub best_variation: Vec<FiveInRowMove>,
+}
+
+impl MoveAnalysis {
+    /// The move turned a winning or neutral position into a lost one,
+    /// or it missed a forced win
+    pub fn is_blunder(&self) -> bool {
+        (self.score_before != Score::Loss && self.score_after == Score::Loss)
+            || (self.score_before == Score::Win && self.score_after != Score::Win)
+    }
+}
+
+/// Re-runs the search before and after each of our moves
+pub fn analyse_game(game: &LoadedGame, depth: u8) -> Result<Vec<MoveAnalysis>, Error> {
+    let mut analyses = Vec::new();
+    for (ply, played) in game.moves.iter().enumerate() {
+        if !played.is_mine() {
+            continue;
+        }
+        let before = game.get_position(ply);
+        if before.get_winning_line().is_some() {
+            break;
+        }
+        let best = match evaluate(&before, depth)? {
+            Some(best) => best,
+            None => continue,
+        };
+        let after = game.get_position(ply + 1);
+        let score_after = if after.get_winning_line().is_some() {
+            Score::Win
+        } else {
+            evaluate(&after, depth)?.map_or(Score::Numeric(0.0), |s| s.get_deep_score())
+        };
+        analyses.push(MoveAnalysis {
+            ply,
+            played: *played,
+            score_before: best.get_deep_score(),
+            score_after,
+            best: *best.get_move(),
+            best_variation: best.get_principal_variation(),
+        });
+    }
+    Ok(analyses)
+}
+
+pub fn format_report(analyses: &[MoveAnalysis]) -> String {
+    let mut out = String::new();
+    for analysis in analyses.iter() {
+        let _ = write!(
+            out,
+            "{:>3}. {:<4} {:?} -> {:?}",
+            analysis.ply + 1,
+            render::notation(&analysis.played),
+            analysis.score_before,
+            analysis.score_after
+        );
+        if analysis.is_blunder() {
+            let variation = analysis
+                .best_variation
+                .iter()
+                .map(render::notation)
+                .collect::<Vec<_>>()
+                .join(" ");
+            let _ = write!(
+                out,
+                "  BLUNDER, better: {} ({})",
+                render::notation(&analysis.best),
+                variation
+            );
+        }
+        out.push('\n');
+    }
+    let blunders = analyses.iter().filter(|a| a.is_blunder()).count();
+    let _ = writeln!(
+        out,
+        "{} moves analysed, {} blunder(s) found",
+        analyses.len(),
+        blunders
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_finds_missed_block() {
+        // we ignore the rival's open four
+        let game = LoadedGame::from_moves(vec![
+            FiveInRowMove::Mine(0, 5),
+            FiveInRowMove::Rivals(0, 0),
+            FiveInRowMove::Mine(2, 5),
+            FiveInRowMove::Rivals(1, 0),
+            FiveInRowMove::Mine(4, 5),
+            FiveInRowMove::Rivals(2, 0),
+            FiveInRowMove::Mine(-4, 5),
+            FiveInRowMove::Rivals(3, 0),
+            FiveInRowMove::Mine(-6, 5),
+            FiveInRowMove::Rivals(4, 0),
+        ]);
+        let analyses = analyse_game(&game, 3).unwrap();
+        assert_eq!(analyses.len(), 5);
+        let blunders = analyses
+            .iter()
+            .filter(|a| a.is_blunder())
+            .collect::<Vec<_>>();
+        assert!(!blunders.is_empty());
+        assert!(blunders.iter().all(|a| a.ply == 6));
+        assert_ne!(blunders[0].best, blunders[0].played);
+        assert!(format_report(&analyses).contains("BLUNDER"));
+    }
+}
@@ -0,0 +1,65 @@
+use crate::analysis::{evaluate_for, is_my_turn, Error, LoadedGame};
+use crate::five_in_a_row::book::OpeningBook;
+use crate::five_in_a_row::FiveInRow;
+use crate::game::Game;
+
+/// Adds the opening moves of the winners of the games, returns the number of
+/// games with a known result
+pub fn add_games(book: &mut OpeningBook, games: &[LoadedGame], max_plies: usize) -> usize {
+    let mut count = 0;
+    for game in games.iter() {
+        if let Some(won) = game.is_won() {
+            book.add_game(&game.moves, won, max_plies);
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Searches the position and adds the `width` best moves to the book, the
+/// best one with the highest weight. The positions after these moves are
+/// expanded the same way until they have `max_stones` stones.
+pub fn expand(
+    book: &mut OpeningBook,
+    position: &FiveInRow,
+    depth: u8,
+    max_stones: usize,
+    width: usize,
+) -> Result<(), Error> {
+    if position.moves.len() >= max_stones || position.get_winning_line().is_some() {
+        return Ok(());
+    }
+    let suggestions = evaluate_for(position, is_my_turn(position), depth)?;
+    let best = suggestions.iter().take(width).collect::<Vec<_>>();
+    for (rank, suggestion) in best.iter().enumerate() {
+        let mv = *suggestion.get_move();
+        book.add(position, &mv, (best.len() - rank) as u32);
+        let mut next = position.clone();
+        next.do_move(mv)?;
+        expand(book, &next, depth, max_stones, width)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::position::parse_moves;
+
+    #[test]
+    fn it_builds_book() {
+        let mut book = OpeningBook::default();
+        let games = vec![
+            LoadedGame::from_moves(parse_moves("h8 i9 h9 j10 h10 g7 h11 k11 h12", true).unwrap()),
+            LoadedGame::from_moves(parse_moves("h8 i9", true).unwrap()),
+        ];
+        assert_eq!(add_games(&mut book, &games, 4), 1);
+        assert_eq!(book.positions.len(), 2);
+
+        let mut book = OpeningBook::default();
+        expand(&mut book, &FiveInRow::create_empty(), 0, 3, 2).unwrap();
+        let moves = book.get_moves(&FiveInRow::create_empty());
+        assert_eq!(moves.len(), 1);
+        assert!(book.positions.len() > 2);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::analysis::{evaluate_for, Error};
+use crate::five_in_a_row::mv::FiveInRowMove;
+use crate::five_in_a_row::render::{self, RenderOptions};
+use crate::five_in_a_row::threats::{self, Threat};
+use crate::five_in_a_row::FiveInRow;
+use crate::game::{score::Score, Game};
+use crate::gameplay::suggestion::Suggestion;
+use std::fmt::Write;
+
+/// Parses a point given in the standard notation (`h8`) or as `x,y` coordinates
+pub fn parse_position(token: &str) -> Option<(i32, i32)> {
+    if let Some((x, y)) = token.split_once(',') {
+        return Some((x.trim().parse().ok()?, y.trim().parse().ok()?));
+    }
+    render::parse_notation(token)
+}
+
+/// Parses a list of alternating moves, each given either in the standard
+/// notation (`h8`) or as `x,y` coordinates, separated by whitespace
+pub fn parse_moves(input: &str, first_mine: bool) -> Result<Vec<FiveInRowMove>, Error> {
+    input
+        .split_whitespace()
+        .enumerate()
+        .map(|(i, token)| {
+            let (x, y) = parse_position(token).ok_or_else(|| Error::Parse(String::from(token)))?;
+            Ok(if (i % 2 == 0) == first_mine {
+                FiveInRowMove::Mine(x, y)
+            } else {
+                FiveInRowMove::Rivals(x, y)
+            })
+        })
+        .collect()
+}
+
+pub struct PositionReport {
+    pub position: FiveInRow,
+    pub my_turn: bool,
+    pub depth: u8,
+    pub score: Score,
+    pub threats: Vec<Threat>,
+    pub candidates: Vec<Suggestion<FiveInRow>>,
+}
+
+/// Evaluates the position and lists up to `count` best candidate moves
+pub fn analyse_position(
+    position: &FiveInRow,
+    my_turn: bool,
+    depth: u8,
+    count: usize,
+) -> Result<PositionReport, Error> {
+    let mut candidates = evaluate_for(position, my_turn, depth)?;
+    candidates.truncate(count);
+    Ok(PositionReport {
+        position: position.clone(),
+        my_turn,
+        depth,
+        score: position.get_score(),
+        threats: threats::find_threats(position),
+        candidates,
+    })
+}
+
+fn format_points(points: &[(i32, i32)]) -> String {
+    points
+        .iter()
+        .map(|(x, y)| render::notation(&FiveInRowMove::Mine(*x, *y)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl PositionReport {
+    pub fn format(&self, options: &RenderOptions) -> String {
+        let mut out = render::render(&self.position, options);
+        let _ = writeln!(
+            out,
+            "To move: {}",
+            if self.my_turn { "X (us)" } else { "O (rival)" }
+        );
+        let _ = writeln!(out, "Static score: {:?}", self.score);
+
+        out.push_str("Threats:\n");
+        if self.threats.is_empty() {
+            out.push_str("  none\n");
+        }
+        for threat in self.threats.iter() {
+            let _ = write!(
+                out,
+                "  {} {:?}: {}",
+                if threat.mine { "X" } else { "O" },
+                threat.kind,
+                format_points(&threat.stones)
+            );
+            if !threat.gains.is_empty() {
+                let _ = write!(out, " (gains {})", format_points(&threat.gains));
+            }
+            out.push('\n');
+        }
+
+        let _ = writeln!(out, "Candidates (depth {}):", self.depth);
+        for (i, candidate) in self.candidates.iter().enumerate() {
+            let variation = candidate
+                .get_principal_variation()
+                .iter()
+                .map(render::notation)
+                .collect::<Vec<_>>()
+                .join(" ");
+            let _ = writeln!(
+                out,
+                "  {}. {:<4} {:?}  {}",
+                i + 1,
+                render::notation(candidate.get_move()),
+                candidate.get_deep_score(),
+                variation
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::five_in_a_row::diagram::DiagramOptions;
+
+    #[test]
+    fn it_parses_moves() {
+        let moves = parse_moves("h8 1,0\n h9", true).unwrap();
+        assert_eq!(
+            moves,
+            vec![
+                FiveInRowMove::Mine(0, 0),
+                FiveInRowMove::Rivals(1, 0),
+                FiveInRowMove::Mine(0, 1),
+            ]
+        );
+        assert_eq!(
+            parse_moves("h8", false).unwrap()[0],
+            FiveInRowMove::Rivals(0, 0)
+        );
+        assert!(matches!(parse_moves("h8 z9", true), Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn it_analyses_position() {
+        let options = DiagramOptions {
+            my_turn: false,
+            ..DiagramOptions::default()
+        };
+        let game = FiveInRow::from_diagram("XXXX.\nOOO..\n", &options).unwrap();
+        let report = analyse_position(&game, false, 0, 3).unwrap();
+        assert!(report.candidates.len() <= 3);
+        assert!(!report.threats.is_empty());
+        let formatted = report.format(&RenderOptions::compact());
+        assert!(formatted.contains("To move: O (rival)"));
+        assert!(formatted.contains("X OpenFour:"));
+    }
+}
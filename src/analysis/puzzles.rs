@@ -0,0 +1,296 @@
+use crate::analysis::position::{parse_moves, parse_position};
+use crate::analysis::{evaluate_for, Error};
+use crate::five_in_a_row::diagram::{DiagramOptions, Origin};
+use crate::five_in_a_row::mv::FiveInRowMove;
+use crate::five_in_a_row::render;
+use crate::five_in_a_row::FiveInRow;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_DEPTH: u8 = 2;
+
+/// Position where we (`X`) are on the move with the set of acceptable answers
+#[derive(Debug, Clone)]
+pub struct Puzzle {
+    pub name: String,
+    pub category: String,
+    pub depth: u8,
+    pub position: FiveInRow,
+    pub answers: Vec<(i32, i32)>,
+    /// The engine is known to fail the puzzle, it doesn't count as a regression
+    pub known_failure: bool,
+}
+
+#[derive(Debug, Clone)]
+struct PuzzleBuilder {
+    name: String,
+    line_no: usize,
+    properties: BTreeMap<String, String>,
+    diagram: String,
+}
+
+impl PuzzleBuilder {
+    fn error(&self, message: &str) -> Error {
+        Error::Parse(format!(
+            "puzzle {} on line {}: {}",
+            self.name, self.line_no, message
+        ))
+    }
+
+    fn points(&self, value: &str) -> Result<Vec<(i32, i32)>, Error> {
+        value
+            .split_whitespace()
+            .map(|p| parse_position(p).ok_or_else(|| self.error(p)))
+            .collect()
+    }
+
+    fn build(self) -> Result<Puzzle, Error> {
+        let category = self
+            .properties
+            .get("category")
+            .ok_or_else(|| self.error("missing category"))?
+            .clone();
+        let depth = match self.properties.get("depth") {
+            Some(depth) => depth.parse().map_err(|_| self.error(depth))?,
+            None => DEFAULT_DEPTH,
+        };
+        let answers = self.points(
+            self.properties
+                .get("answers")
+                .ok_or_else(|| self.error("missing answers"))?,
+        )?;
+        let known_failure = self
+            .properties
+            .get("known-failure")
+            .is_some_and(|v| v == "yes" || v == "true");
+
+        let position = match self.properties.get("moves") {
+            Some(moves) => {
+                // the moves alternate so that the last one is the rival's
+                let count = moves.split_whitespace().count();
+                FiveInRow::from_moves(parse_moves(moves, count % 2 == 0)?)
+            }
+            None => {
+                let origin = match self.properties.get("origin") {
+                    Some(point) => {
+                        let (x, y) = parse_position(point).ok_or_else(|| self.error(point))?;
+                        Origin::TopLeft(x, y)
+                    }
+                    None => Origin::Center,
+                };
+                let options = DiagramOptions {
+                    origin,
+                    my_turn: true,
+                };
+                FiveInRow::from_diagram(&self.diagram, &options)?
+            }
+        };
+        Ok(Puzzle {
+            name: self.name,
+            category,
+            depth,
+            position,
+            answers,
+            known_failure,
+        })
+    }
+}
+
+/// Parses the puzzle file.
+///
+/// Each puzzle starts with its `[name]` followed by `key: value` properties
+/// and the position, given either by a diagram (see `diagram::parse`) or by
+/// the `moves` property. Lines starting with `#` are comments.
+///
+/// Properties: `category` and `answers` (points in the standard notation or
+/// as `x,y` coordinates) are required, `depth`, `origin` (top left cell of
+/// the diagram) and `known-failure` are optional.
+pub fn parse(content: &str) -> Result<Vec<Puzzle>, Error> {
+    let mut puzzles = Vec::new();
+    let mut current: Option<PuzzleBuilder> = None;
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            if let Some(builder) = current.take() {
+                puzzles.push(builder.build()?);
+            }
+            current = Some(PuzzleBuilder {
+                name: String::from(name.trim()),
+                line_no: i + 1,
+                properties: BTreeMap::new(),
+                diagram: String::new(),
+            });
+            continue;
+        }
+        let builder = current
+            .as_mut()
+            .ok_or_else(|| Error::Parse(format!("line {} is outside of a puzzle", i + 1)))?;
+        match line.split_once(':') {
+            Some((key, value)) => {
+                builder
+                    .properties
+                    .insert(String::from(key.trim()), String::from(value.trim()));
+            }
+            None => {
+                builder.diagram.push_str(line);
+                builder.diagram.push('\n');
+            }
+        }
+    }
+    if let Some(builder) = current {
+        puzzles.push(builder.build()?);
+    }
+    Ok(puzzles)
+}
+
+pub fn load(path: &Path) -> Result<Vec<Puzzle>, Error> {
+    let content = fs::read_to_string(path).map_err(|e| Error::Parse(e.to_string()))?;
+    parse(&content)
+}
+
+#[derive(Debug, Clone)]
+pub struct PuzzleResult {
+    pub name: String,
+    pub category: String,
+    pub suggested: Option<FiveInRowMove>,
+    pub solved: bool,
+    pub known_failure: bool,
+    pub duration: Duration,
+}
+
+impl PuzzleResult {
+    /// The puzzle is expected to be solved but it wasn't
+    pub fn is_regression(&self) -> bool {
+        !self.solved && !self.known_failure
+    }
+}
+
+pub fn solve(puzzle: &Puzzle) -> Result<PuzzleResult, Error> {
+    let started = Instant::now();
+    let suggested = evaluate_for(&puzzle.position, true, puzzle.depth)?
+        .first()
+        .map(|s| *s.get_move());
+    let solved = suggested.is_some_and(|mv| puzzle.answers.contains(&(mv.get_x(), mv.get_y())));
+    Ok(PuzzleResult {
+        name: puzzle.name.clone(),
+        category: puzzle.category.clone(),
+        suggested,
+        solved,
+        known_failure: puzzle.known_failure,
+        duration: started.elapsed(),
+    })
+}
+
+/// Lists the result of every puzzle and the solve rate per category
+pub fn format_report(results: &[PuzzleResult]) -> String {
+    let mut out = String::new();
+    for result in results.iter() {
+        let status = match (result.solved, result.known_failure) {
+            (true, _) => "ok",
+            (false, true) => "known failure",
+            (false, false) => "FAILED",
+        };
+        let _ = writeln!(
+            out,
+            "{:<24} {:<20} {:<4} {:>8.2}s  {}",
+            result.name,
+            result.category,
+            result
+                .suggested
+                .as_ref()
+                .map_or(String::from("-"), render::notation),
+            result.duration.as_secs_f64(),
+            status
+        );
+    }
+
+    let mut categories: BTreeMap<&str, (usize, usize, Duration)> = BTreeMap::new();
+    for result in results.iter() {
+        let entry = categories.entry(&result.category).or_default();
+        entry.0 += usize::from(result.solved);
+        entry.1 += 1;
+        entry.2 += result.duration;
+    }
+    out.push('\n');
+    for (category, (solved, total, duration)) in categories.iter() {
+        let _ = writeln!(
+            out,
+            "{:<24} {:>3}/{:<3} {:>4.0}%  {:>8.2}s",
+            category,
+            solved,
+            total,
+            100.0 * *solved as f64 / *total as f64,
+            duration.as_secs_f64()
+        );
+    }
+    let solved = results.iter().filter(|r| r.solved).count();
+    let regressions = results.iter().filter(|r| r.is_regression()).count();
+    let _ = writeln!(
+        out,
+        "{} of {} puzzles solved, {} regression(s)",
+        solved,
+        results.len(),
+        regressions
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_puzzles() {
+        let puzzles = parse(
+            "
+            # comment
+            [five]
+            category: win
+            depth: 0
+            answers: j9 e9
+            . X X X X .
+            O O O . . O
+
+            [block]
+            category: block four
+            answers: 0,4 0,-1
+            known-failure: yes
+            moves: 0,0 1,0 0,1 1,1 0,2 1,2 0,3
+            ",
+        )
+        .unwrap();
+        assert_eq!(puzzles.len(), 2);
+        assert_eq!(puzzles[0].name, "five");
+        assert_eq!(puzzles[0].depth, 0);
+        assert_eq!(puzzles[0].answers, vec![(2, 1), (-3, 1)]);
+        assert_eq!(puzzles[0].position.moves.len(), 8);
+        assert!(!puzzles[0].known_failure);
+        assert_eq!(puzzles[1].depth, DEFAULT_DEPTH);
+        assert!(puzzles[1].known_failure);
+        assert_eq!(
+            puzzles[1].position.moves.last(),
+            Some(&FiveInRowMove::Rivals(0, 3))
+        );
+
+        assert!(parse("category: win").is_err());
+        assert!(parse("[no answers]\ncategory: win\nX").is_err());
+    }
+
+    #[test]
+    fn it_solves_puzzle() {
+        let puzzles =
+            parse("[five]\ncategory: win\ndepth: 0\nanswers: j9 e9\n. X X X X .\nO O O . . O")
+                .unwrap();
+        let result = solve(&puzzles[0]).unwrap();
+        assert!(result.solved);
+        assert!(!result.is_regression());
+        let report = format_report(&[result]);
+        assert!(report.contains("1 of 1 puzzles solved, 0 regression(s)"));
+    }
+}
@@ -0,0 +1,150 @@
+use crate::analysis::{evaluate, Error, LoadedGame};
+use crate::five_in_a_row::mv::FiveInRowMove;
+use crate::five_in_a_row::render::{self, RenderOptions};
+use crate::game::{Game, GameMove};
+use std::fmt::Write;
+
+fn describe_move(mv: &FiveInRowMove) -> String {
+    format!(
+        "{} {} ({}, {})",
+        if mv.is_mine() { "X" } else { "O" },
+        render::notation(mv),
+        mv.get_x(),
+        mv.get_y()
+    )
+}
+
+fn describe_variation(moves: &[FiveInRowMove]) -> String {
+    moves
+        .iter()
+        .map(render::notation)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Steps through a recorded game and shows the engine's opinion at every ply
+pub struct Replay {
+    game: LoadedGame,
+    ply: usize,
+    pub depth: u8,
+    pub render_options: RenderOptions,
+}
+
+impl Replay {
+    pub fn new(game: LoadedGame, depth: u8) -> Self {
+        Self {
+            game,
+            ply: 0,
+            depth,
+            render_options: RenderOptions::default(),
+        }
+    }
+
+    /// Number of moves played before the current position
+    pub fn get_ply(&self) -> usize {
+        self.ply
+    }
+
+    pub fn len(&self) -> usize {
+        self.game.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.game.moves.is_empty()
+    }
+
+    pub fn forward(&mut self) -> bool {
+        self.goto(self.ply + 1)
+    }
+
+    pub fn back(&mut self) -> bool {
+        self.ply > 0 && self.goto(self.ply - 1)
+    }
+
+    pub fn goto(&mut self, ply: usize) -> bool {
+        if ply > self.len() {
+            return false;
+        }
+        self.ply = ply;
+        true
+    }
+
+    /// Renders the current position together with the move played from it
+    /// and the move our engine would play instead
+    pub fn describe(&self) -> Result<String, Error> {
+        let position = self.game.get_position(self.ply);
+        let mut out = String::new();
+        let _ = writeln!(out, "Ply {}/{}", self.ply, self.len());
+        if let Some(last) = position.moves.last() {
+            let _ = writeln!(out, "Last move: {}", describe_move(last));
+        }
+        out.push_str(&render::render(&position, &self.render_options));
+        let _ = writeln!(out, "Static score: {:?}", position.get_score());
+
+        if let Some(played) = self.game.moves.get(self.ply) {
+            let _ = write!(out, "Played: {}", describe_move(played));
+            let recorded = self
+                .game
+                .record
+                .as_ref()
+                .and_then(|r| r.moves.get(self.ply));
+            if let Some(recorded) = recorded {
+                if let (Some(score), Some(depth)) = (recorded.score, recorded.depth) {
+                    let _ = write!(out, ", recorded score {:?} at depth {}", score, depth);
+                }
+            }
+            out.push('\n');
+        }
+        if position.get_winning_line().is_some() {
+            out.push_str("The game is finished\n");
+            return Ok(out);
+        }
+        match evaluate(&position, self.depth)? {
+            Some(best) => {
+                let _ = writeln!(
+                    out,
+                    "Engine: {}, score {:?} at depth {}",
+                    describe_move(best.get_move()),
+                    best.get_deep_score(),
+                    self.depth
+                );
+                let _ = writeln!(
+                    out,
+                    "Principal variation: {}",
+                    describe_variation(&best.get_principal_variation())
+                );
+            }
+            None => out.push_str("Engine: no move available\n"),
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_steps_through_game() {
+        let mut replay = Replay::new(
+            LoadedGame::from_moves(vec![
+                FiveInRowMove::Mine(0, 0),
+                FiveInRowMove::Rivals(1, 0),
+                FiveInRowMove::Mine(0, 1),
+            ]),
+            0,
+        );
+        assert_eq!(replay.get_ply(), 0);
+        assert!(!replay.back());
+        assert!(replay.forward());
+        assert!(replay.goto(3));
+        assert!(!replay.forward());
+        assert_eq!(replay.get_ply(), 3);
+        assert!(replay.back());
+
+        let description = replay.describe().unwrap();
+        assert!(description.starts_with("Ply 2/3\nLast move: O I8 (1, 0)\n"));
+        assert!(description.contains("Played: X H9 (0, 1)\n"));
+        assert!(description.contains("Engine: X "));
+    }
+}
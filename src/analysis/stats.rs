@@ -0,0 +1,130 @@
+use crate::game::record::GameRecord;
+
+/// Summary of the results of played games
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stats {
+    pub games: usize,
+    pub wins: usize,
+    pub losses: usize,
+    /// Games which ended without a winner
+    pub errors: usize,
+    pub moves: usize,
+    /// Total duration of the finished games in milliseconds
+    pub duration: u64,
+}
+
+impl Stats {
+    pub fn add<M: Copy>(&mut self, record: &GameRecord<M>) {
+        self.games += 1;
+        self.moves += record.moves.len();
+        if let Some(finished_at) = record.finished_at {
+            self.duration += finished_at.saturating_sub(record.started_at);
+        }
+        match record.is_won() {
+            Some(true) => self.wins += 1,
+            Some(false) => self.losses += 1,
+            None => self.errors += 1,
+        }
+    }
+
+    /// Adds the results of other games
+    pub fn merge(&mut self, other: &Stats) {
+        self.games += other.games;
+        self.wins += other.wins;
+        self.losses += other.losses;
+        self.errors += other.errors;
+        self.moves += other.moves;
+        self.duration += other.duration;
+    }
+
+    /// Share of the wins among the games with a winner
+    pub fn win_rate(&self) -> Option<f64> {
+        let decided = self.wins + self.losses;
+        if decided == 0 {
+            None
+        } else {
+            Some(self.wins as f64 / decided as f64)
+        }
+    }
+
+    pub fn format(&self) -> String {
+        let mut report = String::new();
+        report.push_str(&format!("\t- wins: {}\n", self.wins));
+        report.push_str(&format!("\t- losses: {}\n", self.losses));
+        report.push_str(&format!("\t- errors: {}\n", self.errors));
+        report.push_str(&format!("\t- total games: {}\n", self.games));
+        if let Some(rate) = self.win_rate() {
+            report.push_str(&format!("\t- win rate: {:.1}%\n", rate * 100.0));
+        }
+        if self.games > 0 {
+            report.push_str(&format!(
+                "\t- average moves: {:.1}\n",
+                self.moves as f64 / self.games as f64
+            ));
+            report.push_str(&format!(
+                "\t- average duration: {:.0}s\n",
+                self.duration as f64 / self.games as f64 / 1000.0
+            ));
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::game_connection::GameInfo;
+
+    fn record(winner_id: Option<&str>, moves: usize) -> GameRecord<(i32, i32)> {
+        let mut record = GameRecord::new(GameInfo {
+            user_id: Some(String::from("me")),
+            ..GameInfo::default()
+        });
+        for i in 0..moves {
+            record.add_move((i as i32, 0), None, None);
+        }
+        record.finished_at = Some(record.started_at + 10_000);
+        record.winner_id = winner_id.map(String::from);
+        record
+    }
+
+    #[test]
+    fn it_summarizes_games() {
+        let mut stats = Stats::default();
+        assert_eq!(stats.win_rate(), None);
+        stats.add(&record(Some("me"), 9));
+        stats.add(&record(Some("rival"), 10));
+        stats.add(&record(Some("me"), 11));
+        stats.add(&record(None, 0));
+        assert_eq!(
+            stats,
+            Stats {
+                games: 4,
+                wins: 2,
+                losses: 1,
+                errors: 1,
+                moves: 30,
+                duration: 40_000,
+            }
+        );
+        assert!((stats.win_rate().unwrap() - 2.0 / 3.0).abs() < 1e-9);
+        let report = stats.format();
+        assert!(report.contains("win rate: 66.7%"));
+        assert!(report.contains("average moves: 7.5"));
+    }
+
+    #[test]
+    fn it_merges_stats() {
+        let mut first = Stats::default();
+        first.add(&record(Some("me"), 9));
+        let mut second = Stats::default();
+        second.add(&record(Some("rival"), 10));
+        second.add(&record(None, 0));
+        first.merge(&second);
+        assert_eq!(
+            (first.games, first.wins, first.losses, first.errors),
+            (3, 1, 1, 1)
+        );
+        assert_eq!(first.moves, 19);
+    }
+}
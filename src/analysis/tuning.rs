@@ -0,0 +1,216 @@
+use crate::analysis::LoadedGame;
+use crate::five_in_a_row::eval::EvalParams;
+use crate::five_in_a_row::FiveInRow;
+use crate::game::score::Score;
+
+/// Position of a finished game with the final result from our point of view
+#[derive(Debug, Clone)]
+pub struct TrainingPosition {
+    pub position: FiveInRow,
+    /// 1 for our win, 0 for our loss
+    pub result: f64,
+}
+
+/// Result of the game from our point of view, `None` when it is unknown
+fn get_result(game: &LoadedGame) -> Option<f64> {
+    Some(if game.is_won()? { 1.0 } else { 0.0 })
+}
+
+/// Takes every position of the finished games except the first `skip_plies`
+/// ones and the final one
+pub fn collect_positions(games: &[LoadedGame], skip_plies: usize) -> Vec<TrainingPosition> {
+    let mut positions = Vec::new();
+    for game in games.iter() {
+        let result = match get_result(game) {
+            Some(result) => result,
+            None => continue,
+        };
+        for ply in skip_plies.max(1)..game.moves.len() {
+            let position = game.get_position(ply);
+            if position.get_winning_line().is_some() {
+                break;
+            }
+            positions.push(TrainingPosition { position, result });
+        }
+    }
+    positions
+}
+
+/// Expected result of the position with the static score, `scale` converts
+/// the score to the logistic curve
+pub fn win_probability(score: Score, scale: f64) -> f64 {
+    match score {
+        Score::Win => 1.0,
+        Score::Loss => 0.0,
+        Score::Numeric(n) => 1.0 / (1.0 + (-scale * n).exp()),
+    }
+}
+
+/// Mean squared error of the predicted results
+pub fn get_error(positions: &[TrainingPosition], params: &EvalParams, scale: f64) -> f64 {
+    if positions.is_empty() {
+        return 0.0;
+    }
+    let total = positions
+        .iter()
+        .map(|p| {
+            let predicted = win_probability(p.position.get_score_with(params), scale);
+            (p.result - predicted).powi(2)
+        })
+        .sum::<f64>();
+    total / positions.len() as f64
+}
+
+/// Finds the scale with the lowest error by a ternary search over its logarithm
+pub fn fit_scale(positions: &[TrainingPosition], params: &EvalParams) -> f64 {
+    let scores = positions
+        .iter()
+        .map(|p| (p.position.get_score_with(params), p.result))
+        .collect::<Vec<_>>();
+    let error = |log_scale: f64| {
+        let scale = 10f64.powf(log_scale);
+        scores
+            .iter()
+            .map(|(score, result)| (result - win_probability(*score, scale)).powi(2))
+            .sum::<f64>()
+    };
+    let (mut low, mut high) = (-6.0, 1.0);
+    for _ in 0..50 {
+        let left = low + (high - low) / 3.0;
+        let right = high - (high - low) / 3.0;
+        if error(left) < error(right) {
+            high = right;
+        } else {
+            low = left;
+        }
+    }
+    10f64.powf((low + high) / 2.0)
+}
+
+fn to_weights(params: &EvalParams) -> Vec<f64> {
+    vec![
+        params.five,
+        params.four,
+        params.three,
+        params.two,
+        params.closed_gap[0],
+        params.closed_gap[1],
+        params.closed_gap[2],
+        params.rival_factor,
+    ]
+}
+
+fn from_weights(weights: &[f64]) -> EvalParams {
+    EvalParams {
+        five: weights[0],
+        four: weights[1],
+        three: weights[2],
+        two: weights[3],
+        closed_gap: [
+            weights[4].min(1.0),
+            weights[5].min(1.0),
+            weights[6].min(1.0),
+        ],
+        rival_factor: weights[7],
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TuningResult {
+    pub params: EvalParams,
+    pub scale: f64,
+    pub initial_error: f64,
+    pub error: f64,
+    pub iterations: usize,
+}
+
+/// Texel style local search: each weight is in turn increased and decreased
+/// by the relative step while the error decreases, the step is halved when
+/// no weight changes.
+///
+/// The scale of the logistic curve is fitted to the initial weights and kept.
+pub fn tune(
+    positions: &[TrainingPosition],
+    initial: &EvalParams,
+    max_iterations: usize,
+) -> TuningResult {
+    let scale = fit_scale(positions, initial);
+    let mut weights = to_weights(initial);
+    let initial_error = get_error(positions, initial, scale);
+    let mut error = initial_error;
+    let mut step = 0.2;
+    let mut iterations = 0;
+    while iterations < max_iterations && step > 0.005 {
+        iterations += 1;
+        let mut improved = false;
+        for i in 0..weights.len() {
+            for factor in [1.0 + step, 1.0 - step].iter() {
+                let mut candidate = weights.clone();
+                candidate[i] *= factor;
+                let candidate_error = get_error(positions, &from_weights(&candidate), scale);
+                if candidate_error < error {
+                    weights = candidate;
+                    error = candidate_error;
+                    improved = true;
+                    break;
+                }
+            }
+        }
+        if !improved {
+            step /= 2.0;
+        }
+    }
+    TuningResult {
+        params: from_weights(&weights),
+        scale,
+        initial_error,
+        error,
+        iterations,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::position::parse_moves;
+
+    fn games() -> Vec<LoadedGame> {
+        [
+            // we win with a vertical five
+            "h8 i9 h9 j10 h10 g7 h11 k11 h7 l12 h6",
+            // the rival wins with a diagonal five
+            "h8 i9 a1 j10 a3 k11 a5 l12 a7 m13",
+            // unfinished game
+            "h8 i9 h9",
+        ]
+        .iter()
+        .map(|moves| LoadedGame::from_moves(parse_moves(moves, true).unwrap()))
+        .collect()
+    }
+
+    #[test]
+    fn it_collects_positions_of_finished_games() {
+        let games = games();
+        assert_eq!(get_result(&games[0]), Some(1.0));
+        assert_eq!(get_result(&games[1]), Some(0.0));
+        assert_eq!(get_result(&games[2]), None);
+        let positions = collect_positions(&games, 2);
+        assert_eq!(positions.len(), 8 + 7);
+        assert_eq!(positions[0].position.moves.len(), 2);
+    }
+
+    #[test]
+    fn it_tunes_weights() {
+        let positions = collect_positions(&games(), 0);
+        let params = EvalParams::default();
+        let result = tune(&positions, &params, 10);
+        assert!(result.scale > 0.0);
+        assert!(result.error <= result.initial_error);
+        assert!(result.iterations <= 10);
+        assert!(result.params.closed_gap.iter().all(|g| *g <= 1.0));
+        assert_eq!(
+            result.initial_error,
+            get_error(&positions, &params, result.scale)
+        );
+    }
+}
@@ -2,3 +2,7 @@
 pub mod game_connection;
 #[path = "jobs_cz/jobs_cz.rs"]
 pub mod jobs_cz;
+#[path = "rate_limiter.rs"]
+pub mod rate_limiter;
+#[path = "retry.rs"]
+pub mod retry;
@@ -1,9 +1,47 @@
 use crate::game::{error::Error, Game};
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// Identification of the game and its players as provided by the server
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GameInfo {
+    pub game_token: Option<String>,
+    pub user_id: Option<String>,
+    pub player_cross_id: Option<String>,
+    pub player_circle_id: Option<String>,
+}
+
+/// Connection used for offline analysis where no server is available
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoConnection;
 
 #[async_trait]
-pub trait GameConnection<G: Game> {
+pub trait GameConnection<G: Game>: Send {
     async fn start_game(&mut self) -> Result<G, Error<G>>;
     async fn put_move(&mut self, mv: &G::Move) -> Result<(), Error<G>>;
     async fn await_move(&mut self) -> Result<(Option<G::Move>, Option<String>), Error<G>>;
+    /// The game as known to the server with the settings of the local game,
+    /// used to recover from `Error::Desync`
+    async fn get_server_game(&mut self, _game: &G) -> Result<Option<G>, Error<G>> {
+        Ok(None)
+    }
+    fn get_game_info(&self) -> GameInfo {
+        GameInfo::default()
+    }
+}
+
+#[async_trait]
+impl<G: Game + Send + 'static> GameConnection<G> for NoConnection
+where
+    G::Move: Sync,
+{
+    async fn start_game(&mut self) -> Result<G, Error<G>> {
+        Err(Error::Invalid)
+    }
+    async fn put_move(&mut self, _: &G::Move) -> Result<(), Error<G>> {
+        Err(Error::Invalid)
+    }
+    async fn await_move(&mut self) -> Result<(Option<G::Move>, Option<String>), Error<G>> {
+        Err(Error::Invalid)
+    }
 }
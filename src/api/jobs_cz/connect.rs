@@ -15,12 +15,12 @@ pub struct ConnectResponse {
     pub gameId: String,
 }
 
+/// Every processed request starts a new game, only failures which left the
+/// request unprocessed are repeated
 pub async fn invoke_connection(
     client: &mut fetch::JobsApi,
     payload: &ConnectPayload,
 ) -> Result<ConnectResponse, fetch::Error> {
-    let res: ConnectResponse = client
-        .post_data("https://piskvorky.jobs.cz/api/v1/connect", payload)
-        .await?;
+    let res: ConnectResponse = client.post_action("/api/v1/connect", payload).await?;
     Ok(res)
 }
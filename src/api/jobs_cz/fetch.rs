@@ -1,21 +1,66 @@
+use crate::api::jobs_cz::transport::{
+    HttpTransport, RecordingTransport, ReplayTransport, Transport,
+};
+use crate::api::rate_limiter::RateLimiter;
+use crate::api::retry::RetryPolicy;
+use reqwest::StatusCode;
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::json;
-use std::convert::TryFrom;
 use std::error::Error as StdError;
 use std::fmt::{self, Display, Formatter};
-use std::thread::sleep;
+use std::path::Path;
+use std::sync::Arc;
 use std::time::Duration;
-use std::time::Instant;
+use tokio::time::sleep;
+
+/// Minimal spacing of the requests allowed by the server
+const REQUEST_INTERVAL: Duration = Duration::from_millis(1100);
+
+pub const DEFAULT_BASE_URL: &str = "https://piskvorky.jobs.cz";
 
 #[derive(Debug)]
 pub enum Error {
     ApiErr(reqwest::Error),
     JsonErr(serde_json::Error),
     ParseError,
-    TimeError(std::num::TryFromIntError),
     RivalTimeoutError,
     FinishedUnexpectedly,
     Invalid,
+    /// Response with an error status and its body
+    Status(StatusCode, String),
+    /// The last error of a request which failed even after retrying
+    Request {
+        url: String,
+        attempts: u32,
+        error: Box<Error>,
+    },
+    /// Transport error replayed from a recording
+    Transport(String),
+    /// The requests don't match the replayed recording
+    Replay(String),
+}
+impl Error {
+    /// Transport errors, server errors, too many requests and malformed
+    /// responses may pass when the request is repeated
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::ApiErr(_) | Self::JsonErr(_) | Self::Transport(_) => true,
+            Self::Status(status, _) => {
+                status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => false,
+        }
+    }
+
+    /// Failures of requests which surely weren't processed by the server, only
+    /// those may be repeated when the request isn't idempotent
+    pub fn is_retryable_unprocessed(&self) -> bool {
+        match self {
+            Self::ApiErr(e) => e.is_connect(),
+            Self::Status(status, _) => *status == StatusCode::TOO_MANY_REQUESTS,
+            _ => false,
+        }
+    }
 }
 impl From<reqwest::Error> for Error {
     fn from(e: reqwest::Error) -> Self {
@@ -27,21 +72,27 @@ impl From<serde_json::Error> for Error {
         Self::JsonErr(e)
     }
 }
-impl From<std::num::TryFromIntError> for Error {
-    fn from(e: std::num::TryFromIntError) -> Self {
-        Self::TimeError(e)
-    }
-}
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
-            Self::ApiErr(_) => write!(f, "Api Error"),
-            Self::JsonErr(_) => write!(f, "Json Error"),
-            Self::TimeError(_) => write!(f, "Time Error"),
+            Self::ApiErr(e) => write!(f, "Api Error ({})", e),
+            Self::JsonErr(e) => write!(f, "Json Error ({})", e),
             Self::ParseError => write!(f, "Parse Error"),
             Self::RivalTimeoutError => write!(f, "Rival Time Out Error"),
             Self::FinishedUnexpectedly => write!(f, "Finished unexpectedly"),
             Self::Invalid => write!(f, "Invalid request or auth"),
+            Self::Status(status, body) => write!(f, "Http Error {} ({})", status, body),
+            Self::Request {
+                url,
+                attempts,
+                error,
+            } => write!(
+                f,
+                "Request to {} failed after {} attempt(s): {}",
+                url, attempts, error
+            ),
+            Self::Transport(e) => write!(f, "Transport Error ({})", e),
+            Self::Replay(e) => write!(f, "Replay Error ({})", e),
         }
     }
 }
@@ -50,59 +101,324 @@ impl StdError for Error {
         match self {
             Self::ApiErr(err) => Some(err),
             Self::JsonErr(err) => Some(err),
-            Self::TimeError(err) => Some(err),
             Self::ParseError => None,
             Self::RivalTimeoutError => None,
             Self::FinishedUnexpectedly => None,
             Self::Invalid => None,
+            Self::Status(_, _) => None,
+            Self::Request { error, .. } => Some(error.as_ref()),
+            Self::Transport(_) => None,
+            Self::Replay(_) => None,
+        }
+    }
+}
+
+/// Connection settings of the http client
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    /// Address of the server without the `/api/v1` path
+    pub base_url: String,
+    /// Timeout of the whole request
+    pub timeout: Option<Duration>,
+    pub connect_timeout: Option<Duration>,
+    /// Proxy used for all the requests, the system proxy by default
+    pub proxy: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            base_url: String::from(DEFAULT_BASE_URL),
+            timeout: Some(Duration::from_secs(30)),
+            connect_timeout: Some(Duration::from_secs(10)),
+            proxy: None,
+            user_agent: Some(format!("five-in-a-row/{}", env!("CARGO_PKG_VERSION"))),
         }
     }
 }
 
+impl Settings {
+    pub fn build_client(&self) -> Result<reqwest::Client, Error> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(proxy) = self.proxy.as_ref() {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+        if let Some(user_agent) = self.user_agent.as_ref() {
+            builder = builder.user_agent(user_agent);
+        }
+        Ok(builder.build()?)
+    }
+}
+
+/// Client of the server, the clones share the rate limit
 #[derive(Debug, Clone)]
 pub struct JobsApi {
-    client: reqwest::Client,
-    time: Instant,
-    last_call: i64,
+    transport: Arc<dyn Transport>,
+    base_url: String,
+    limiter: RateLimiter,
+    retry: RetryPolicy,
 }
 
 impl JobsApi {
     pub fn new(client: reqwest::Client) -> Self {
         Self {
-            client,
-            time: Instant::now(),
-            last_call: 0,
+            transport: Arc::new(HttpTransport::new(client)),
+            base_url: String::from(DEFAULT_BASE_URL),
+            limiter: RateLimiter::new(1, REQUEST_INTERVAL),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    pub fn from_settings(settings: &Settings) -> Result<Self, Error> {
+        Ok(Self::new(settings.build_client()?).with_base_url(&settings.base_url))
+    }
+
+    /// Client serving the recorded session without the server and without
+    /// waiting between the requests
+    pub fn from_recording(path: &Path) -> Result<Self, Error> {
+        Ok(Self::new(reqwest::Client::new())
+            .with_transport(Arc::new(ReplayTransport::load(path)?))
+            .with_limiter(RateLimiter::new(1, Duration::from_micros(1)))
+            .with_retry(RetryPolicy {
+                initial_delay: Duration::from_millis(0),
+                max_delay: Duration::from_millis(0),
+                ..RetryPolicy::default()
+            }))
+    }
+
+    pub fn with_transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Appends all the following requests and responses to the file
+    pub fn record_to(self, path: &Path) -> std::io::Result<Self> {
+        let transport = RecordingTransport::new(self.transport.clone(), path)?;
+        Ok(self.with_transport(Arc::new(transport)))
+    }
+
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = String::from(base_url.trim_end_matches('/'));
+        self
+    }
+
+    pub fn with_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sends the request once
+    async fn send<R: DeserializeOwned>(&self, url: &str, body: &str) -> Result<R, Error> {
+        let (status, response_text) = self.transport.post(url, body).await?;
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            self.limiter.throttle();
         }
+        if !status.is_success() {
+            return Err(Error::Status(status, response_text));
+        }
+        Ok(serde_json::from_str(&response_text)?)
     }
 
+    /// Posts the payload to the path relative to the base url, the retryable
+    /// failures are repeated according to the retry policy
     pub async fn post_data<P: Serialize + std::fmt::Debug, R: DeserializeOwned>(
         &mut self,
-        url: &str,
+        path: &str,
+        payload: P,
+    ) -> Result<R, Error> {
+        self.post_with_retry(path, payload, Error::is_retryable)
+            .await
+    }
+
+    /// Posts a request which must not be processed twice, it's repeated only
+    /// when it didn't reach the server or was rejected by the rate limit
+    pub async fn post_action<P: Serialize + std::fmt::Debug, R: DeserializeOwned>(
+        &mut self,
+        path: &str,
+        payload: P,
+    ) -> Result<R, Error> {
+        self.post_with_retry(path, payload, Error::is_retryable_unprocessed)
+            .await
+    }
+
+    async fn post_with_retry<P: Serialize + std::fmt::Debug, R: DeserializeOwned>(
+        &mut self,
+        path: &str,
         payload: P,
+        is_retryable: fn(&Error) -> bool,
     ) -> Result<R, Error> {
-        let body = json!(payload);
-        let remaining: i64 =
-            1000 - (i64::try_from(self.time.elapsed().as_millis())? - self.last_call);
-        if remaining > 0 {
-            sleep(Duration::from_millis(u64::try_from(remaining + 100)?));
+        let url = format!("{}{}", self.base_url, path);
+        let body = json!(payload).to_string();
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            self.limiter.acquire().await;
+            let error = match self.send(&url, &body).await {
+                Ok(res) => return Ok(res),
+                Err(error) => error,
+            };
+            if !is_retryable(&error) || attempts >= self.retry.max_attempts {
+                return Err(Error::Request {
+                    url,
+                    attempts,
+                    error: Box::new(error),
+                });
+            }
+            let delay = self.retry.get_delay(attempts, &mut rand::thread_rng());
+            sleep(delay).await;
         }
-        //println!("\n\n====================\nRequest: {}\n{:?}", url, payload);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    async fn read_request(stream: &mut TcpStream) {
+        let mut request = Vec::new();
+        let mut buffer = [0u8; 1024];
         loop {
-            let maybe_response = self.client.post(url).body(body.to_string()).send().await;
-
-            if let Ok(response) = maybe_response {
-                if response.status() == 429 {
-                    // println!("...waiting");
-                    sleep(Duration::from_millis(1100));
-                    continue;
-                } else {
-                    let response_text = response.text().await?;
-                    // println!("====================\nResponse:\n{}", response_text);
-                    let res: R = serde_json::from_str(&response_text)?;
-                    self.last_call = i64::try_from(self.time.elapsed().as_millis())?;
-                    return Ok(res);
+            let read = stream.read(&mut buffer).await.unwrap();
+            request.extend_from_slice(&buffer[..read]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some(end) = text.find("\r\n\r\n") {
+                let length = text
+                    .lines()
+                    .find_map(|l| {
+                        l.to_lowercase()
+                            .strip_prefix("content-length:")
+                            .map(String::from)
+                    })
+                    .map_or(0, |l| l.trim().parse().unwrap());
+                if request.len() >= end + 4 + length || read == 0 {
+                    return;
                 }
             }
         }
     }
+
+    /// Answers the requests with the responses in turn, returns the url and
+    /// the counter of the requests
+    async fn serve(responses: Vec<(u16, &'static str)>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = count.clone();
+        tokio::spawn(async move {
+            for (status, body) in responses.into_iter() {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                read_request(&mut stream).await;
+                counter.fetch_add(1, Ordering::SeqCst);
+                let response = format!(
+                    "HTTP/1.1 {} X\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, count)
+    }
+
+    fn client(base_url: &str) -> JobsApi {
+        JobsApi::from_settings(&Settings {
+            base_url: String::from(base_url),
+            ..Settings::default()
+        })
+        .unwrap()
+        .with_limiter(RateLimiter::new(10, Duration::from_millis(1)))
+        .with_retry(RetryPolicy {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn it_retries_server_errors() {
+        let (url, count) = serve(vec![(500, ""), (429, ""), (200, r#"{"a": 1}"#)]).await;
+        let res: Value = client(&url)
+            .post_data("/api/v1/test", json!({}))
+            .await
+            .unwrap();
+        assert_eq!(res["a"], 1);
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn it_gives_up_after_max_attempts() {
+        let (url, count) = serve(vec![(503, ""), (200, "not json"), (502, "down")]).await;
+        let err = client(&url)
+            .post_data::<_, Value>("/api/v1/test", json!({}))
+            .await
+            .unwrap_err();
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+        match &err {
+            Error::Request {
+                attempts, error, ..
+            } => {
+                assert_eq!(*attempts, 3);
+                assert!(matches!(**error, Error::Status(StatusCode::BAD_GATEWAY, _)));
+            }
+            e => panic!("Unexpected error {:?}", e),
+        }
+        assert!(err.to_string().contains("failed after 3 attempt(s)"));
+    }
+
+    #[tokio::test]
+    async fn it_does_not_retry_client_errors() {
+        let (url, count) = serve(vec![(401, "unauthorized"), (200, "{}")]).await;
+        let err = client(&url)
+            .post_data::<_, Value>("/api/v1/test", json!({}))
+            .await
+            .unwrap_err();
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert!(!err.is_retryable());
+        assert!(err.to_string().contains("unauthorized"));
+    }
+
+    #[tokio::test]
+    async fn it_repeats_actions_only_when_not_processed() {
+        let (url, count) = serve(vec![(429, ""), (200, r#"{"a": 1}"#)]).await;
+        let res: Value = client(&url)
+            .post_action("/api/v1/test", json!({}))
+            .await
+            .unwrap();
+        assert_eq!(res["a"], 1);
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+
+        // the server may have processed the request before failing
+        let (url, count) = serve(vec![(500, ""), (200, "{}")]).await;
+        let err = client(&url)
+            .post_action::<_, Value>("/api/v1/test", json!({}))
+            .await
+            .unwrap_err();
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert!(err.to_string().contains("failed after 1 attempt(s)"));
+
+        let (url, count) = serve(vec![(200, "not json"), (200, "{}")]).await;
+        client(&url)
+            .post_action::<_, Value>("/api/v1/test", json!({}))
+            .await
+            .unwrap_err();
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
 }
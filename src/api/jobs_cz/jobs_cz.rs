@@ -10,11 +10,40 @@ pub mod player;
 pub mod register;
 #[path = "status.rs"]
 pub mod status;
+#[path = "transport.rs"]
+pub mod transport;
 
-use crate::api::game_connection::GameConnection;
+use crate::api::game_connection::{GameConnection, GameInfo};
+use crate::api::retry::RetryPolicy;
 use crate::five_in_a_row::{mv::FiveInRowMove, FiveInRow};
-use crate::game::error::Error;
+use crate::game::{error::Error, log::GameLog, GameMove};
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Number of the rival's moves after which the board is compared with the
+/// server by default
+pub const DEFAULT_VERIFY_INTERVAL: usize = 10;
+
+/// Game in progress stored to be resumed after a restart
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub user_id: String,
+    pub game_token: String,
+}
+
+impl SavedGame {
+    /// The missing or unreadable file gives no game
+    pub fn load(path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        fs::write(path, serde_json::to_string(self)?)
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct JobsApi {
@@ -22,6 +51,20 @@ pub struct JobsApi {
     user_token: String,
     client: fetch::JobsApi,
     game_token: Option<String>,
+    player_cross_id: Option<String>,
+    player_circle_id: Option<String>,
+    /// Moves of the game known to the client
+    moves: Vec<FiveInRowMove>,
+    /// The board is compared with the server after this number of the
+    /// rival's moves, `None` verifies only the unexpected statuses
+    verify_interval: Option<usize>,
+    /// Rival's moves awaited since the last verification
+    unverified_moves: usize,
+    /// Game continued by the next `start_game` instead of connecting
+    resume_game_token: Option<String>,
+    /// File keeping the token of the game in progress
+    game_file: Option<PathBuf>,
+    log: GameLog,
 }
 
 impl JobsApi {
@@ -31,13 +74,171 @@ impl JobsApi {
             user_id: String::from(user_id),
             user_token: String::from(user_token),
             game_token: None,
+            player_cross_id: None,
+            player_circle_id: None,
+            moves: Vec::new(),
+            verify_interval: Some(DEFAULT_VERIFY_INTERVAL),
+            unverified_moves: 0,
+            resume_game_token: None,
+            game_file: None,
+            log: GameLog::new(),
+        }
+    }
+
+    /// Uses the client with its connection settings, its clones share
+    /// the rate limit
+    pub fn with_client(mut self, client: fetch::JobsApi) -> Self {
+        self.client = client;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.client = self.client.with_retry(retry);
+        self
+    }
+
+    pub fn with_log(mut self, log: GameLog) -> Self {
+        self.log = log;
+        self
+    }
+
+    pub fn with_verify_interval(mut self, verify_interval: Option<usize>) -> Self {
+        self.verify_interval = verify_interval;
+        self
+    }
+
+    /// Continues the game on the next `start_game` instead of connecting
+    /// to a new one, `None` connects unless a game is stored
+    pub fn with_game_token(mut self, game_token: Option<String>) -> Self {
+        self.resume_game_token = game_token;
+        self
+    }
+
+    /// Stores the token of the game in progress to the file, `start_game`
+    /// resumes the stored game of the user until it finishes
+    pub fn with_game_file(mut self, path: &Path) -> Self {
+        self.game_file = Some(path.to_path_buf());
+        self
+    }
+
+    fn get_saved_game_token(&self) -> Option<String> {
+        SavedGame::load(self.game_file.as_ref()?)
+            .filter(|saved| saved.user_id == self.user_id)
+            .map(|saved| saved.game_token)
+    }
+
+    fn save_game(&self) {
+        if let (Some(path), Some(game_token)) = (self.game_file.as_ref(), self.game_token.as_ref())
+        {
+            let saved = SavedGame {
+                user_id: self.user_id.clone(),
+                game_token: game_token.clone(),
+            };
+            if let Err(e) = saved.save(path) {
+                self.log
+                    .print(&format!("Unable to save the game token: {}", e));
+            }
+        }
+    }
+
+    fn remove_saved_game(&self) {
+        if let Some(path) = self.game_file.as_ref() {
+            if self.get_saved_game_token() == self.game_token {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+
+    fn get_status_payload(&self) -> Result<status::StatusPayload, Error<FiveInRow>> {
+        let game_token = self.game_token.as_ref().ok_or(Error::ApiInvalidData)?;
+        Ok(status::StatusPayload {
+            gameToken: game_token.clone(),
+            userToken: String::from(&self.user_token),
+        })
+    }
+
+    /// All the moves of the game on the server
+    async fn fetch_server_moves(
+        &mut self,
+    ) -> Result<(Vec<FiveInRowMove>, Option<String>), Error<FiveInRow>> {
+        let status_payload = self.get_status_payload()?;
+        let stat_data = status::fetch_status(&mut self.client, &status_payload).await?;
+        self.player_cross_id = stat_data.playerCrossId;
+        self.player_circle_id = stat_data.playerCircleId;
+        let moves = FiveInRow::from_api_coordinates(stat_data.coordinates, &self.user_id).moves;
+        Ok((moves, stat_data.winnerId))
+    }
+
+    /// Rebuilds the game from all the moves played so far
+    async fn load_game(&mut self) -> Result<FiveInRow, Error<FiveInRow>> {
+        let (moves, winner_id) = self.fetch_server_moves().await?;
+        if winner_id.is_some() {
+            return Err(Error::FinishedUnexpectedly);
+        }
+        self.moves = moves.clone();
+        self.unverified_moves = 0;
+        Ok(FiveInRow::from_moves(moves))
+    }
+
+    /// Compares the known moves with the server, the board may differ only
+    /// by the rival's next move
+    async fn verify_moves(&mut self) -> Result<Option<FiveInRowMove>, Error<FiveInRow>> {
+        let (moves, _) = self.fetch_server_moves().await?;
+        self.unverified_moves = 0;
+        let known = self.moves.len();
+        if moves.len() >= known && moves[..known] == self.moves[..] {
+            match moves[known..] {
+                [] => return Ok(None),
+                [mv] if !mv.is_mine() => {
+                    self.moves.push(mv);
+                    return Ok(Some(mv));
+                }
+                _ => {}
+            }
         }
+        Err(Error::Desync {
+            local: known,
+            server: moves.len(),
+        })
+    }
+
+    async fn resume_game(&mut self, game_token: String) -> Result<FiveInRow, Error<FiveInRow>> {
+        self.game_token = Some(game_token);
+        let result = self.load_game().await;
+        match result.as_ref() {
+            Ok(game) => self.log.print(&format!(
+                "Resumed the game {} after {} moves",
+                self.game_token.as_deref().unwrap_or_default(),
+                game.moves.len()
+            )),
+            // the game may still be resumed when the server is available
+            Err(Error::ApiError(fetch::Error::Request { error, .. })) if error.is_retryable() => {}
+            Err(_) => {
+                self.remove_saved_game();
+                self.game_token = None;
+            }
+        }
+        result
     }
 }
 
 #[async_trait]
 impl GameConnection<FiveInRow> for JobsApi {
     async fn start_game(&mut self) -> Result<FiveInRow, Error<FiveInRow>> {
+        let resume_game_token = self
+            .resume_game_token
+            .take()
+            .or_else(|| self.get_saved_game_token());
+        if let Some(game_token) = resume_game_token {
+            match self.resume_game(game_token).await {
+                Ok(game) => return Ok(game),
+                Err(e) if self.game_token.is_some() => return Err(e),
+                Err(e) => self.log.print(&format!(
+                    "Unable to resume the game ({}), starting a new one",
+                    e
+                )),
+            }
+        }
         let con_data = connect::invoke_connection(
             &mut self.client,
             &connect::ConnectPayload {
@@ -45,50 +246,106 @@ impl GameConnection<FiveInRow> for JobsApi {
             },
         )
         .await?;
-        self.game_token = Some(String::from(con_data.gameToken));
-        let game_token = self.game_token.as_ref().ok_or(Error::ApiInvalidData)?;
-
-        println!("Connected, game token: {}", game_token);
-        let status_payload = status::StatusPayload {
-            gameToken: game_token.clone(),
-            userToken: String::from(&self.user_token),
-        };
-
-        let stat_data = status::fetch_status(&mut self.client, &status_payload).await?;
-        let game = FiveInRow::from_api_coordinates(stat_data.coordinates, &self.user_id);
-
-        Ok(game)
+        self.log
+            .print(&format!("Connected, game token: {}", con_data.gameToken));
+        self.game_token = Some(con_data.gameToken);
+        self.save_game();
+        self.load_game().await
     }
 
     async fn put_move(&mut self, mv: &FiveInRowMove) -> Result<(), Error<FiveInRow>> {
         let game_token = self.game_token.as_ref().ok_or(Error::ApiInvalidData)?;
-        play::invoke_move(
-            &mut self.client,
-            &play::PlayPayload {
-                userToken: String::from(&self.user_token),
-                gameToken: String::from(game_token),
-                positionX: mv.get_x(),
-                positionY: mv.get_y(),
-            },
-        )
-        .await?;
+        let payload = play::PlayPayload {
+            userToken: String::from(&self.user_token),
+            gameToken: String::from(game_token),
+            positionX: mv.get_x(),
+            positionY: mv.get_y(),
+        };
+        if let Err(e) = play::invoke_move(&mut self.client, &payload).await {
+            // the server may have accepted the move before the failure
+            let maybe_accepted = match &e {
+                fetch::Error::Request { error, .. } => error.is_retryable(),
+                _ => false,
+            };
+            if !maybe_accepted {
+                return Err(e.into());
+            }
+            let (moves, _) = self.fetch_server_moves().await?;
+            // the rival may have replied already
+            if moves.get(self.moves.len()) != Some(mv) {
+                self.log.print(&format!(
+                    "The move was not accepted ({}), sending it again",
+                    e
+                ));
+                play::invoke_move(&mut self.client, &payload).await?;
+            }
+        }
+        self.moves.push(*mv);
         Ok(())
     }
 
     async fn await_move(
         &mut self,
     ) -> Result<(Option<FiveInRowMove>, Option<String>), Error<FiveInRow>> {
-        let game_token = self.game_token.as_ref().ok_or(Error::ApiInvalidData)?;
-        let status_payload = status::StatusPayload {
-            gameToken: game_token.clone(),
-            userToken: String::from(&self.user_token),
-        };
+        let status_payload = self.get_status_payload()?;
         let stat_data =
-            status::wait_my_turn(&mut self.client, &self.user_id, &status_payload).await?;
-        let rivals_move = stat_data.coordinates.get(0).map_or(None, |coord| {
-            Some(FiveInRowMove::from_api_coordinates(&self.user_id, coord))
-        });
+            status::wait_my_turn(&mut self.client, &self.user_id, &status_payload, &self.log)
+                .await?;
+        self.player_cross_id = stat_data.playerCrossId;
+        self.player_circle_id = stat_data.playerCircleId;
+        let last_move = stat_data
+            .coordinates
+            .first()
+            .map(|coord| FiveInRowMove::from_api_coordinates(&self.user_id, coord));
+        let last_known = self.moves.last().copied();
         let winner_id = stat_data.winnerId;
-        Ok((rivals_move, winner_id))
+        if winner_id.is_some() {
+            self.remove_saved_game();
+            // the game may have been finished by the rival's winning move
+            let rivals_move = last_move.filter(|mv| !mv.is_mine() && Some(*mv) != last_known);
+            self.moves.extend(rivals_move);
+            return Ok((rivals_move, winner_id));
+        }
+        // on our turn the last move is the rival's one, the new one follows ours
+        let expected = match last_move {
+            Some(mv) if mv.is_mine() => None,
+            Some(mv) if Some(mv) == last_known => Some(None),
+            Some(mv) if last_known.is_none_or(|known| known.is_mine()) => Some(Some(mv)),
+            Some(_) => None,
+            None if last_known.is_none() => Some(None),
+            None => None,
+        };
+        self.unverified_moves += 1;
+        let verify = self
+            .verify_interval
+            .is_some_and(|interval| self.unverified_moves >= interval);
+        match expected {
+            Some(rivals_move) if !verify => {
+                self.moves.extend(rivals_move);
+                Ok((rivals_move, None))
+            }
+            _ => Ok((self.verify_moves().await?, None)),
+        }
+    }
+
+    async fn get_server_game(
+        &mut self,
+        game: &FiveInRow,
+    ) -> Result<Option<FiveInRow>, Error<FiveInRow>> {
+        let (moves, _) = self.fetch_server_moves().await?;
+        self.moves = moves.clone();
+        self.unverified_moves = 0;
+        Ok(Some(
+            FiveInRow::from_moves(moves).with_params(game.params.clone()),
+        ))
+    }
+
+    fn get_game_info(&self) -> GameInfo {
+        GameInfo {
+            game_token: self.game_token.clone(),
+            user_id: Some(self.user_id.clone()),
+            player_cross_id: self.player_cross_id.clone(),
+            player_circle_id: self.player_circle_id.clone(),
+        }
     }
 }
@@ -19,13 +19,13 @@ pub struct Coordinate {
     y: i32,
 }
 
+/// Only failures which left the move unprocessed are repeated, the server
+/// would reject a move it already accepted
 pub async fn invoke_move(
     client: &mut fetch::JobsApi,
     payload: &PlayPayload,
 ) -> Result<status::StatusResponse, fetch::Error> {
-    let res: status::StatusResponse = client
-        .post_data("https://piskvorky.jobs.cz/api/v1/play", payload)
-        .await?;
+    let res: status::StatusResponse = client.post_action("/api/v1/play", payload).await?;
     Ok(res)
 }
 
@@ -19,7 +19,8 @@ pub struct Response {
 #[allow(dead_code)]
 pub async fn fetch_player(payload: &Payload) -> Result<Response, fetch::Error> {
     let url = format!(
-        "https://piskvorky.jobs.cz/detail-hrace/{}/",
+        "{}/detail-hrace/{}/",
+        fetch::DEFAULT_BASE_URL,
         payload.user_id
     );
     let body = reqwest::get(url).await?.text().await?;
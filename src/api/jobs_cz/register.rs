@@ -16,13 +16,12 @@ pub struct RegisterResponse {
     pub userToken: String,
 }
 
-#[allow(dead_code)]
+/// Only failures which left the request unprocessed are repeated, the
+/// repeated request would be rejected as the nickname is taken already
 pub async fn invoke_registration(
     client: &mut fetch::JobsApi,
     payload: &RegisterPayload,
 ) -> Result<RegisterResponse, fetch::Error> {
-    let res: RegisterResponse = client
-        .post_data("https://piskvorky.jobs.cz/api/v1/user", payload)
-        .await?;
+    let res: RegisterResponse = client.post_action("/api/v1/user", payload).await?;
     Ok(res)
 }
@@ -1,4 +1,5 @@
 use crate::api::jobs_cz::fetch;
+use crate::game::log::GameLog;
 use serde::{Deserialize, Serialize};
 use std::time::Instant;
 use std::vec::Vec;
@@ -33,9 +34,7 @@ pub async fn fetch_status(
     client: &mut fetch::JobsApi,
     payload: &StatusPayload,
 ) -> Result<StatusResponse, fetch::Error> {
-    let res: StatusResponse = client
-        .post_data("https://piskvorky.jobs.cz/api/v1/checkStatus", payload)
-        .await?;
+    let res: StatusResponse = client.post_data("/api/v1/checkStatus", payload).await?;
     Ok(res)
 }
 
@@ -43,9 +42,7 @@ pub async fn fetch_last_status(
     client: &mut fetch::JobsApi,
     payload: &StatusPayload,
 ) -> Result<StatusResponse, fetch::Error> {
-    let res: StatusResponse = client
-        .post_data("https://piskvorky.jobs.cz/api/v1/checkLastStatus", payload)
-        .await?;
+    let res: StatusResponse = client.post_data("/api/v1/checkLastStatus", payload).await?;
     Ok(res)
 }
 
@@ -53,6 +50,7 @@ pub async fn wait_my_turn(
     client: &mut fetch::JobsApi,
     player_id: &str,
     payload: &StatusPayload,
+    log: &GameLog,
 ) -> Result<StatusResponse, fetch::Error> {
     let time = Instant::now();
     let mut reported = false;
@@ -70,7 +68,7 @@ pub async fn wait_my_turn(
             } else {
                 if !reported {
                     reported = true;
-                    println!("Waiting for rival's move...");
+                    log.print("Waiting for rival's move...");
                 }
                 if time.elapsed().as_secs() > 300 {
                     return Err(fetch::Error::RivalTimeoutError);
@@ -79,7 +77,7 @@ pub async fn wait_my_turn(
         } else {
             if !reported {
                 reported = true;
-                println!("Waiting for rival to connect...");
+                log.print("Waiting for rival to connect...");
             }
             if time.elapsed().as_secs() > 900 {
                 return Err(fetch::Error::RivalTimeoutError);
@@ -0,0 +1,334 @@
+use crate::api::jobs_cz::fetch::Error;
+use async_trait::async_trait;
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Value stored in place of the user tokens in the recordings
+pub const REDACTED: &str = "<redacted>";
+
+/// Sends the request bodies to the server and returns the status and the
+/// body of the response
+#[async_trait]
+pub trait Transport: Debug + Send + Sync {
+    async fn post(&self, url: &str, body: &str) -> Result<(StatusCode, String), Error>;
+}
+
+/// Transport over http
+#[derive(Debug, Clone)]
+pub struct HttpTransport {
+    client: reqwest::Client,
+}
+
+impl HttpTransport {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Transport for HttpTransport {
+    async fn post(&self, url: &str, body: &str) -> Result<(StatusCode, String), Error> {
+        let response = self.client.post(url).body(body.to_string()).send().await?;
+        let status = response.status();
+        Ok((status, response.text().await?))
+    }
+}
+
+/// A request and its outcome, one json line of a recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Exchange {
+    pub url: String,
+    pub request: String,
+    /// Missing when the request failed without a response
+    pub status: Option<u16>,
+    pub response: Option<String>,
+    /// Transport error of the failed request
+    pub error: Option<String>,
+}
+
+/// Replaces the user tokens of a json body, other bodies are kept
+fn redact(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(Value::Object(mut object)) if object.contains_key("userToken") => {
+            object.insert(String::from("userToken"), Value::from(REDACTED));
+            Value::Object(object).to_string()
+        }
+        _ => String::from(body),
+    }
+}
+
+/// Whether the redacted body matches the recorded one, json bodies are
+/// compared regardless of the order of their fields
+fn is_same_request(recorded: &str, body: &str) -> bool {
+    let body = redact(body);
+    match (
+        serde_json::from_str::<Value>(recorded),
+        serde_json::from_str::<Value>(&body),
+    ) {
+        (Ok(recorded), Ok(body)) => recorded == body,
+        _ => recorded == body,
+    }
+}
+
+/// Path of the url, the recordings are replayed regardless of the server
+fn get_path(url: &str) -> String {
+    Url::parse(url)
+        .map(|u| String::from(u.path()))
+        .unwrap_or_else(|_| String::from(url))
+}
+
+/// Passes the requests to the inner transport and appends the exchanges
+/// to a file, the user tokens are redacted
+#[derive(Debug, Clone)]
+pub struct RecordingTransport {
+    inner: Arc<dyn Transport>,
+    file: Arc<Mutex<File>>,
+}
+
+impl RecordingTransport {
+    pub fn new(inner: Arc<dyn Transport>, path: &Path) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            inner,
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    fn write(&self, exchange: &Exchange) {
+        let line = serde_json::to_string(exchange).unwrap();
+        let mut file = self.file.lock().unwrap();
+        if let Err(e) = writeln!(file, "{}", line).and_then(|_| file.flush()) {
+            println!("Unable to record the request: {}", e);
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for RecordingTransport {
+    async fn post(&self, url: &str, body: &str) -> Result<(StatusCode, String), Error> {
+        let result = self.inner.post(url, body).await;
+        let mut exchange = Exchange {
+            url: String::from(url),
+            request: redact(body),
+            status: None,
+            response: None,
+            error: None,
+        };
+        match &result {
+            Ok((status, response)) => {
+                exchange.status = Some(status.as_u16());
+                exchange.response = Some(redact(response));
+            }
+            Err(e) => exchange.error = Some(e.to_string()),
+        }
+        self.write(&exchange);
+        result
+    }
+}
+
+/// Serves the recorded exchanges in their order instead of the server, the
+/// paths and the bodies of the requests have to match the recording
+#[derive(Debug, Clone)]
+pub struct ReplayTransport {
+    exchanges: Arc<Mutex<VecDeque<Exchange>>>,
+}
+
+impl ReplayTransport {
+    pub fn new(exchanges: Vec<Exchange>) -> Self {
+        Self {
+            exchanges: Arc::new(Mutex::new(exchanges.into())),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let exchanges = fs::read_to_string(path)
+            .map_err(|e| Error::Replay(format!("unable to read {:?}: {}", path, e)))?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<Exchange>, _>>()?;
+        Ok(Self::new(exchanges))
+    }
+
+    /// Number of the exchanges not replayed yet
+    pub fn remaining(&self) -> usize {
+        self.exchanges.lock().unwrap().len()
+    }
+}
+
+#[async_trait]
+impl Transport for ReplayTransport {
+    async fn post(&self, url: &str, body: &str) -> Result<(StatusCode, String), Error> {
+        let exchange = self
+            .exchanges
+            .lock()
+            .unwrap()
+            .pop_front()
+            .ok_or_else(|| Error::Replay(format!("no recorded request left for {}", url)))?;
+        if get_path(&exchange.url) != get_path(url) {
+            return Err(Error::Replay(format!(
+                "expected request to {}, got {}",
+                exchange.url, url
+            )));
+        }
+        if !is_same_request(&exchange.request, body) {
+            return Err(Error::Replay(format!(
+                "expected request {} to {}, got {}",
+                exchange.request,
+                url,
+                redact(body)
+            )));
+        }
+        match (exchange.status, exchange.response, exchange.error) {
+            (Some(status), Some(response), _) => {
+                let status = StatusCode::from_u16(status)
+                    .map_err(|_| Error::Replay(format!("invalid status {}", status)))?;
+                Ok((status, response))
+            }
+            (_, _, error) => Err(Error::Transport(error.unwrap_or_default())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::jobs_cz::{connect, fetch, register};
+    use crate::api::rate_limiter::RateLimiter;
+    use crate::server::{state::Options, MockServer};
+    use std::time::Duration;
+
+    fn exchange(url: &str, status: Option<u16>, response: &str) -> Exchange {
+        Exchange {
+            url: String::from(url),
+            request: String::from("{}"),
+            status,
+            response: status.map(|_| String::from(response)),
+            error: status.map_or(Some(String::from(response)), |_| None),
+        }
+    }
+
+    #[test]
+    fn it_redacts_tokens() {
+        assert_eq!(
+            redact(r#"{"userToken":"secret","x":1}"#),
+            format!(r#"{{"userToken":"{}","x":1}}"#, REDACTED)
+        );
+        assert_eq!(redact("not json"), "not json");
+        assert_eq!(get_path("http://127.0.0.1:80/api/v1/play"), "/api/v1/play");
+    }
+
+    #[tokio::test]
+    async fn it_replays_exchanges_in_order() {
+        let transport = ReplayTransport::new(vec![
+            exchange("http://a/api/v1/connect", Some(200), "{}"),
+            exchange("http://a/api/v1/play", None, "timed out"),
+            exchange("http://a/api/v1/play", Some(429), "slow down"),
+        ]);
+        let (status, body) = transport
+            .post("http://b/api/v1/connect", "{}")
+            .await
+            .unwrap();
+        assert_eq!((status, body.as_str()), (StatusCode::OK, "{}"));
+        let err = transport
+            .post("http://b/api/v1/play", "{}")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Transport(_)) && err.is_retryable());
+        let err = transport
+            .post("http://b/api/v1/connect", "{}")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Replay(_)) && !err.is_retryable());
+        let err = transport
+            .post("http://b/api/v1/play", "{}")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no recorded request"));
+    }
+
+    #[tokio::test]
+    async fn it_rejects_different_requests() {
+        let recorded = vec![Exchange {
+            url: String::from("http://a/api/v1/play"),
+            request: format!(
+                r#"{{"userToken":"{}","gameToken":"g","positionX":0,"positionY":1}}"#,
+                REDACTED
+            ),
+            status: Some(201),
+            response: Some(String::from("{}")),
+            error: None,
+        }];
+        let err = ReplayTransport::new(recorded.clone())
+            .post(
+                "http://b/api/v1/play",
+                r#"{"userToken":"secret","gameToken":"g","positionX":1,"positionY":1}"#,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Replay(_)));
+        assert!(!err.to_string().contains("secret"));
+
+        let (status, _) = ReplayTransport::new(recorded)
+            .post(
+                "http://b/api/v1/play",
+                r#"{"positionY":1,"positionX":0,"gameToken":"g","userToken":"secret"}"#,
+            )
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    async fn play_session(client: &mut fetch::JobsApi) -> (String, String, String) {
+        let user = register::invoke_registration(
+            client,
+            &register::RegisterPayload {
+                nickname: String::from("recorded"),
+                email: String::from("recorded@example.com"),
+            },
+        )
+        .await
+        .unwrap();
+        let game = connect::invoke_connection(
+            client,
+            &connect::ConnectPayload {
+                userToken: user.userToken.clone(),
+            },
+        )
+        .await
+        .unwrap();
+        (user.userId, user.userToken, game.gameId)
+    }
+
+    #[tokio::test]
+    async fn it_records_and_replays_session() {
+        let server = MockServer::start(Options::default()).await.unwrap();
+        let path = std::env::temp_dir().join(format!(
+            "five_in_a_row_recording_{}.jsonl",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        let mut client = fetch::JobsApi::new(reqwest::Client::new())
+            .with_base_url(&server.get_base_url())
+            .with_limiter(RateLimiter::new(1, Duration::from_millis(1)))
+            .record_to(&path)
+            .unwrap();
+        let (user_id, user_token, game_id) = play_session(&mut client).await;
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(!content.contains(&user_token));
+        drop(server);
+
+        let mut client = fetch::JobsApi::from_recording(&path).unwrap();
+        let replayed = play_session(&mut client).await;
+        assert_eq!(replayed, (user_id, String::from(REDACTED), game_id));
+        fs::remove_file(&path).unwrap();
+    }
+}
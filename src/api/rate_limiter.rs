@@ -0,0 +1,87 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::{sleep_until, Instant};
+
+#[derive(Debug)]
+struct Bucket {
+    /// Available requests, negative when they are reserved by waiting callers
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token bucket limiting the rate of requests, the clones share the bucket.
+///
+/// A token is added every `interval` up to `capacity`. The callers never
+/// block the thread, they wait asynchronously for their reserved token.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    bucket: Arc<Mutex<Bucket>>,
+    capacity: f64,
+    interval: Duration,
+}
+
+impl RateLimiter {
+    pub fn new(capacity: u32, interval: Duration) -> Self {
+        Self {
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: f64::from(capacity),
+                updated: Instant::now(),
+            })),
+            capacity: f64::from(capacity),
+            interval,
+        }
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let added = now.duration_since(bucket.updated).as_secs_f64() / self.interval.as_secs_f64();
+        bucket.tokens = (bucket.tokens + added).min(self.capacity);
+        bucket.updated = now;
+    }
+
+    /// Reserves a token and waits until it becomes available
+    pub async fn acquire(&self) {
+        let ready_at = {
+            let mut bucket = self.bucket.lock().unwrap();
+            self.refill(&mut bucket);
+            bucket.tokens -= 1.0;
+            if bucket.tokens >= 0.0 {
+                return;
+            }
+            bucket.updated + self.interval.mul_f64(-bucket.tokens)
+        };
+        sleep_until(ready_at).await;
+    }
+
+    /// Drops the available tokens, used when the server reports too many
+    /// requests
+    pub fn throttle(&self) {
+        let mut bucket = self.bucket.lock().unwrap();
+        self.refill(&mut bucket);
+        bucket.tokens = bucket.tokens.min(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn it_spaces_requests() {
+        let limiter = RateLimiter::new(2, Duration::from_millis(50));
+        let started = Instant::now();
+        limiter.acquire().await;
+        limiter.clone().acquire().await;
+        assert!(started.elapsed() < Duration::from_millis(40));
+
+        // the clones share the bucket, the reserved tokens come one by one
+        let clone = limiter.clone();
+        futures::join!(limiter.acquire(), clone.acquire());
+        assert!(started.elapsed() >= Duration::from_millis(100));
+
+        limiter.throttle();
+        let throttled = Instant::now();
+        limiter.acquire().await;
+        assert!(throttled.elapsed() >= Duration::from_millis(40));
+    }
+}
@@ -0,0 +1,77 @@
+use rand::Rng;
+use std::convert::TryFrom;
+use std::time::Duration;
+
+/// How many times and how late the failed requests are repeated
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Number of attempts including the first one
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    /// The delay grows by this factor after each failed attempt
+    pub multiplier: f64,
+    /// Relative random deviation of the delay, 0.2 gives delays within ±20%
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 6,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy which gives up after the first failure
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before the next attempt after `attempt` attempts failed
+    pub fn get_delay<R: Rng>(&self, attempt: u32, rng: &mut R) -> Duration {
+        let exponent = i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX);
+        let delay = (self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent))
+            .min(self.max_delay.as_secs_f64());
+        let jitter = if self.jitter > 0.0 {
+            rng.gen_range(-self.jitter..=self.jitter)
+        } else {
+            0.0
+        };
+        Duration::from_secs_f64(delay * (1.0 + jitter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn it_backs_off_exponentially() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let policy = RetryPolicy {
+            jitter: 0.0,
+            ..RetryPolicy::default()
+        };
+        let delays = (1..=7)
+            .map(|attempt| policy.get_delay(attempt, &mut rng).as_millis())
+            .collect::<Vec<_>>();
+        assert_eq!(delays, vec![500, 1000, 2000, 4000, 8000, 10000, 10000]);
+
+        let policy = RetryPolicy::default();
+        for _ in 0..100 {
+            let delay = policy.get_delay(2, &mut rng).as_millis();
+            assert!((800..=1200).contains(&delay));
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use clap::Parser;
+use game_play::analysis::{blunders, load_game};
+use std::error::Error;
+use std::path::PathBuf;
+
+/// Re-runs a deep search at every ply of a recorded game and reports our
+/// moves which turned a winning or neutral position into a lost one
+#[derive(Parser)]
+struct Args {
+    /// Game record or psq file
+    file: PathBuf,
+    /// Search depth used to evaluate the positions
+    #[arg(short, long, default_value_t = 4)]
+    depth: u8,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    let game = load_game(&args.file)?;
+    let analyses = blunders::analyse_game(&game, args.depth)?;
+    print!("{}", blunders::format_report(&analyses));
+    Ok(())
+}
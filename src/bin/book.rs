@@ -0,0 +1,75 @@
+use clap::{Parser, Subcommand};
+use game_play::analysis::{find_game_files, load_game, opening};
+use game_play::five_in_a_row::book::OpeningBook;
+use game_play::five_in_a_row::FiveInRow;
+use std::error::Error;
+use std::path::PathBuf;
+
+/// Builds the opening book, new moves are added to the existing book file
+#[derive(Parser)]
+struct Args {
+    /// Opening book file
+    #[arg(short, long, default_value = "book.json")]
+    book: PathBuf,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Adds the opening moves of the winners of recorded games
+    Build {
+        /// Game records, psq files or directories containing them
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Number of plies taken from each game
+        #[arg(long, default_value_t = 8)]
+        plies: usize,
+    },
+    /// Adds the best moves found by the engine, starting from the empty board
+    Analyse {
+        /// Search depth
+        #[arg(short, long, default_value_t = 4)]
+        depth: u8,
+        /// Positions with this many stones are not expanded
+        #[arg(long, default_value_t = 4)]
+        plies: usize,
+        /// Number of moves added for each position
+        #[arg(long, default_value_t = 2)]
+        width: usize,
+    },
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    let mut book = if args.book.exists() {
+        OpeningBook::load(&args.book)?
+    } else {
+        OpeningBook::default()
+    };
+    match args.command {
+        Command::Build { paths, plies } => {
+            let mut games = Vec::new();
+            for file in find_game_files(&paths)?.iter() {
+                match load_game(file) {
+                    Ok(game) => games.push(game),
+                    Err(e) => println!("Skipping {} ({})", file.display(), e),
+                }
+            }
+            let count = opening::add_games(&mut book, &games, plies);
+            println!("{} of {} games have a winner", count, games.len());
+        }
+        Command::Analyse {
+            depth,
+            plies,
+            width,
+        } => opening::expand(&mut book, &FiveInRow::create_empty(), depth, plies, width)?,
+    }
+    book.save(&args.book)?;
+    println!(
+        "{} positions saved to {}",
+        book.positions.len(),
+        args.book.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use clap::Parser;
+use game_play::analysis::is_my_turn;
+use game_play::analysis::position::{analyse_position, parse_moves, parse_position};
+use game_play::analysis::Error as AnalysisError;
+use game_play::five_in_a_row::diagram::{DiagramOptions, Origin};
+use game_play::five_in_a_row::eval::EvalParams;
+use game_play::five_in_a_row::svg::{self, SvgOptions};
+use game_play::five_in_a_row::{render::RenderOptions, FiveInRow};
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Evaluates a position given as a board diagram or as a list of moves
+#[derive(Parser)]
+struct Args {
+    /// File with a diagram of `X`, `O` and `.` rows, `-` reads the diagram from stdin
+    #[arg(long, conflicts_with = "moves")]
+    diagram: Option<PathBuf>,
+    /// Alternating moves in the standard notation (`h8`) or as `x,y` coordinates
+    #[arg(long)]
+    moves: Option<String>,
+    /// The rival (`O`) played the first move of the move list
+    #[arg(long)]
+    rival_first: bool,
+    /// The rival (`O`) is on the move in the diagram
+    #[arg(long)]
+    rival_to_move: bool,
+    /// Point of the top left cell of the diagram (`a15` or `x,y`), the diagram
+    /// is centered on h8 by default
+    #[arg(long)]
+    top_left: Option<String>,
+    /// Search depth
+    #[arg(short, long, default_value_t = 4)]
+    depth: u8,
+    /// Number of candidate moves to show
+    #[arg(short = 'n', long, default_value_t = 5)]
+    top: usize,
+    /// Evaluation weights (`.toml` or json file)
+    #[arg(short, long)]
+    weights: Option<PathBuf>,
+    /// Use ANSI colours
+    #[arg(long)]
+    color: bool,
+    /// Save a diagram of the position as SVG
+    #[arg(long)]
+    svg: Option<PathBuf>,
+    /// Save a diagram of the position as PNG
+    #[cfg(feature = "png")]
+    #[arg(long)]
+    png: Option<PathBuf>,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    let (position, my_turn) = match (&args.diagram, &args.moves) {
+        (Some(path), _) => {
+            let content = if path.to_str() == Some("-") {
+                let mut content = String::new();
+                io::stdin().read_to_string(&mut content)?;
+                content
+            } else {
+                fs::read_to_string(path)?
+            };
+            let origin = match &args.top_left {
+                Some(point) => {
+                    let (x, y) =
+                        parse_position(point).ok_or_else(|| AnalysisError::Parse(point.clone()))?;
+                    Origin::TopLeft(x, y)
+                }
+                None => Origin::Center,
+            };
+            let options = DiagramOptions {
+                origin,
+                my_turn: !args.rival_to_move,
+            };
+            (
+                FiveInRow::from_diagram(&content, &options)?,
+                !args.rival_to_move,
+            )
+        }
+        (None, Some(moves)) => {
+            let position = FiveInRow::from_moves(parse_moves(moves, !args.rival_first)?);
+            let my_turn = is_my_turn(&position);
+            (position, my_turn)
+        }
+        (None, None) => return Err("Either --diagram or --moves is required".into()),
+    };
+
+    let position = match &args.weights {
+        Some(path) => position.with_params(Arc::new(EvalParams::load(path)?)),
+        None => position,
+    };
+    let report = analyse_position(&position, my_turn, args.depth, args.top)?;
+    let options = if args.color {
+        RenderOptions::terminal()
+    } else {
+        RenderOptions::default()
+    };
+    print!("{}", report.format(&options));
+    if let Some(path) = args.svg.as_ref() {
+        svg::save_svg(&position, &SvgOptions::default(), path)?;
+    }
+    #[cfg(feature = "png")]
+    if let Some(path) = args.png.as_ref() {
+        svg::save_png(&position, &SvgOptions::default(), path)?;
+    }
+    Ok(())
+}
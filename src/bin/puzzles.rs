@@ -0,0 +1,54 @@
+use clap::Parser;
+use game_play::analysis::puzzles;
+use game_play::five_in_a_row::eval::EvalParams;
+use std::error::Error;
+use std::path::PathBuf;
+use std::process;
+use std::sync::Arc;
+
+/// Runs the engine on a suite of tactical puzzles and reports the solve rate
+/// per category, exits with an error when a puzzle which is not a known
+/// failure is not solved
+#[derive(Parser)]
+struct Args {
+    /// Puzzle file
+    #[arg(default_value = "tests/puzzles.txt")]
+    file: PathBuf,
+    /// Run only the puzzles of the category
+    #[arg(short, long)]
+    category: Option<String>,
+    /// Override the search depth of all puzzles
+    #[arg(short, long)]
+    depth: Option<u8>,
+    /// Evaluation weights (`.toml` or json file)
+    #[arg(short, long)]
+    weights: Option<PathBuf>,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    let params = match &args.weights {
+        Some(path) => Arc::new(EvalParams::load(path)?),
+        None => Arc::default(),
+    };
+    let mut results = Vec::new();
+    for mut puzzle in puzzles::load(&args.file)? {
+        if args
+            .category
+            .as_ref()
+            .is_some_and(|c| *c != puzzle.category)
+        {
+            continue;
+        }
+        puzzle.position = puzzle.position.with_params(params.clone());
+        if let Some(depth) = args.depth {
+            puzzle.depth = depth;
+        }
+        results.push(puzzles::solve(&puzzle)?);
+    }
+    print!("{}", puzzles::format_report(&results));
+    if results.iter().any(|r| r.is_regression()) {
+        process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use clap::Parser;
+use game_play::analysis::{load_game, replay::Replay};
+use game_play::five_in_a_row::render::RenderOptions;
+use std::error::Error;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+/// Steps through a recorded game (`.json` game record or `.psq` file) and shows
+/// what the engine would have played at every ply
+#[derive(Parser)]
+struct Args {
+    /// Game record or psq file
+    file: PathBuf,
+    /// Search depth used for the engine's suggestions
+    #[arg(short, long, default_value_t = 2)]
+    depth: u8,
+    /// Ply to start at
+    #[arg(short, long, default_value_t = 0)]
+    ply: usize,
+    /// Use ANSI colours
+    #[arg(long)]
+    color: bool,
+}
+
+const HELP: &str = "Commands: [n]ext (default), [p]rev, [f]irst, [l]ast, [g]oto <ply>, [q]uit";
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    let game = load_game(&args.file)?;
+    let mut replay = Replay::new(game, args.depth);
+    replay.render_options = if args.color {
+        RenderOptions::terminal()
+    } else {
+        RenderOptions::default()
+    };
+    replay.goto(args.ply);
+
+    println!("{}", HELP);
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        println!("{}", replay.describe()?);
+        print!("> ");
+        io::stdout().flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+        let mut parts = line.split_whitespace();
+        let moved = match parts.next().unwrap_or("n") {
+            "n" | "next" => replay.forward(),
+            "p" | "prev" => replay.back(),
+            "f" | "first" => replay.goto(0),
+            "l" | "last" => replay.goto(replay.len()),
+            "g" | "goto" => parts
+                .next()
+                .and_then(|ply| ply.parse().ok())
+                .is_some_and(|ply| replay.goto(ply)),
+            "q" | "quit" => break,
+            _ => {
+                println!("{}", HELP);
+                false
+            }
+        };
+        if !moved {
+            println!("Cannot move to the requested ply");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use clap::Parser;
+use game_play::server::serve;
+use game_play::server::state::{Options, Rival, ServerState};
+use std::error::Error;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+/// Game server compatible with the piskvorky.jobs.cz api for running bot
+/// leagues, the connecting players are paired with each other
+#[derive(Parser)]
+struct Args {
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    address: SocketAddr,
+    /// File with the users and games, they are kept only in memory without it
+    #[arg(long)]
+    data: Option<PathBuf>,
+    /// Seconds the player on the move has to play, 0 disables the limit
+    #[arg(long, default_value_t = 300.0)]
+    turn_timeout: f64,
+    /// Minimal spacing of the requests of a user in milliseconds
+    #[arg(long, default_value_t = 0)]
+    min_interval: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    let options = Options {
+        rival: Rival::Player,
+        min_interval: Some(Duration::from_millis(args.min_interval)).filter(|d| !d.is_zero()),
+        turn_timeout: Some(Duration::from_secs_f64(args.turn_timeout)).filter(|d| !d.is_zero()),
+        ..Options::default()
+    };
+    let state = match args.data.as_ref() {
+        Some(path) => ServerState::load(path, options)?,
+        None => ServerState::new(options),
+    };
+    println!(
+        "{} users and {} games loaded",
+        state.users.len(),
+        state.games.len()
+    );
+    let listener = TcpListener::bind(args.address).await?;
+    println!("Listening on http://{}", listener.local_addr()?);
+    serve(listener, Arc::new(Mutex::new(state))).await?;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use clap::Parser;
+use game_play::analysis::{find_game_files, load_game, tuning};
+use game_play::five_in_a_row::eval::EvalParams;
+use std::error::Error;
+use std::path::PathBuf;
+
+/// Fits the evaluation weights to the results of recorded games and writes
+/// them into a file which the engine can load
+#[derive(Parser)]
+struct Args {
+    /// Game records, psq files or directories containing them
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+    /// File with the weights to start from, the built-in ones by default
+    #[arg(short, long)]
+    initial: Option<PathBuf>,
+    /// Output file
+    #[arg(short, long, default_value = "weights.json")]
+    output: PathBuf,
+    /// Maximal number of passes over all the weights
+    #[arg(long, default_value_t = 50)]
+    iterations: usize,
+    /// Number of opening plies left out of the training positions
+    #[arg(long, default_value_t = 4)]
+    skip_plies: usize,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    let mut games = Vec::new();
+    for file in find_game_files(&args.paths)?.iter() {
+        match load_game(file) {
+            Ok(game) => games.push(game),
+            Err(e) => println!("Skipping {} ({})", file.display(), e),
+        }
+    }
+
+    let positions = tuning::collect_positions(&games, args.skip_plies);
+    println!(
+        "{} training positions from {} games",
+        positions.len(),
+        games.len()
+    );
+    if positions.is_empty() {
+        return Err("No finished games to learn from".into());
+    }
+    let initial = match &args.initial {
+        Some(path) => EvalParams::load(path)?,
+        None => EvalParams::default(),
+    };
+    let result = tuning::tune(&positions, &initial, args.iterations);
+    println!(
+        "Error {:.5} -> {:.5} after {} iterations (scale {:.6})",
+        result.initial_error, result.error, result.iterations, result.scale
+    );
+    println!("{:#?}", result.params);
+    result.params.save(&args.output)?;
+    println!("Weights saved to {}", args.output.display());
+    Ok(())
+}
@@ -0,0 +1,331 @@
+use crate::api::jobs_cz::fetch;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const DEFAULT_CONFIG_FILE: &str = "piskvorky.toml";
+/// File keeping the token of the game in progress
+pub const DEFAULT_GAME_FILE: &str = "piskvorky-game.json";
+/// File with the credentials of the registered accounts
+pub const DEFAULT_CREDENTIALS_FILE: &str = "piskvorky-credentials.toml";
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    Serialize(toml::ser::Error),
+    MissingCredentials,
+    UnknownProfile(String),
+    ProfileExists(String),
+}
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Self::Toml(e)
+    }
+}
+impl From<toml::ser::Error> for Error {
+    fn from(e: toml::ser::Error) -> Self {
+        Self::Serialize(e)
+    }
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(_) => write!(f, "IO Error"),
+            Self::Toml(e) => write!(f, "Invalid configuration ({})", e),
+            Self::Serialize(e) => write!(f, "Unable to store the credentials ({})", e),
+            Self::MissingCredentials => write!(f, "User id or user token is not configured"),
+            Self::UnknownProfile(name) => write!(f, "No stored profile {}", name),
+            Self::ProfileExists(name) => write!(f, "Profile {} is already stored", name),
+        }
+    }
+}
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Toml(err) => Some(err),
+            Self::Serialize(err) => Some(err),
+            Self::MissingCredentials => None,
+            Self::UnknownProfile(_) => None,
+            Self::ProfileExists(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Credentials {
+    pub user_id: String,
+    pub user_token: String,
+}
+
+/// Credentials of the registered accounts by their profile names, the file
+/// is readable only by its owner
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CredentialStore {
+    /// Profile used when none is selected
+    pub default: Option<String>,
+    pub profiles: BTreeMap<String, Credentials>,
+}
+
+impl CredentialStore {
+    /// Loads the file when it exists, no profiles otherwise
+    pub fn load_or_default(path: &Path) -> Result<Self, Error> {
+        if path.exists() {
+            Ok(toml::from_str(&fs::read_to_string(path)?)?)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Replaces the file, it is created with the permissions of the owner
+    /// only and then renamed
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let content = toml::to_string(self)?;
+        let tmp_path = path.with_extension("tmp");
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            options.mode(0o600);
+            // the mode applies only to the newly created files
+            if tmp_path.exists() {
+                fs::set_permissions(&tmp_path, fs::Permissions::from_mode(0o600))?;
+            }
+        }
+        let mut file = options.open(&tmp_path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Stores the profile, the first one becomes the default
+    pub fn add(&mut self, name: &str, credentials: Credentials) -> Result<(), Error> {
+        if self.profiles.contains_key(name) {
+            return Err(Error::ProfileExists(String::from(name)));
+        }
+        self.profiles.insert(String::from(name), credentials);
+        if self.default.is_none() {
+            self.default = Some(String::from(name));
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Result<Credentials, Error> {
+        self.profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownProfile(String::from(name)))
+    }
+
+    pub fn get_default(&self) -> Option<Credentials> {
+        self.profiles.get(self.default.as_ref()?).cloned()
+    }
+}
+
+/// Connection to the game server, the missing values keep the defaults
+/// of `fetch::Settings`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub base_url: Option<String>,
+    /// Timeout of a request in seconds
+    pub timeout: Option<f64>,
+    /// Timeout of establishing a connection in seconds
+    pub connect_timeout: Option<f64>,
+    pub proxy: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl ServerConfig {
+    /// Values set in `other` take precedence
+    pub fn merge(&self, other: &ServerConfig) -> ServerConfig {
+        ServerConfig {
+            base_url: other.base_url.clone().or_else(|| self.base_url.clone()),
+            timeout: other.timeout.or(self.timeout),
+            connect_timeout: other.connect_timeout.or(self.connect_timeout),
+            proxy: other.proxy.clone().or_else(|| self.proxy.clone()),
+            user_agent: other.user_agent.clone().or_else(|| self.user_agent.clone()),
+        }
+    }
+
+    pub fn get_settings(&self) -> fetch::Settings {
+        let defaults = fetch::Settings::default();
+        fetch::Settings {
+            base_url: self.base_url.clone().unwrap_or(defaults.base_url),
+            timeout: self
+                .timeout
+                .map(Duration::from_secs_f64)
+                .or(defaults.timeout),
+            connect_timeout: self
+                .connect_timeout
+                .map(Duration::from_secs_f64)
+                .or(defaults.connect_timeout),
+            proxy: self.proxy.clone().or(defaults.proxy),
+            user_agent: self.user_agent.clone().or(defaults.user_agent),
+        }
+    }
+}
+
+/// Settings of the game client, the command line options and the
+/// environment variables take precedence over them
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub user_id: Option<String>,
+    pub user_token: Option<String>,
+    pub records_dir: Option<PathBuf>,
+    pub weights: Option<PathBuf>,
+    pub book: Option<PathBuf>,
+    pub game_file: Option<PathBuf>,
+    pub credentials_file: Option<PathBuf>,
+    /// Directory for a log file of each game
+    pub logs_dir: Option<PathBuf>,
+    /// Further accounts playing with `--all-accounts`
+    pub accounts: Vec<Credentials>,
+    pub server: ServerConfig,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Loads the file when it exists, an empty configuration otherwise
+    pub fn load_or_default(path: &Path) -> Result<Self, Error> {
+        if path.exists() {
+            Self::load(path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Credentials given explicitly, the missing values are taken from the
+    /// configuration
+    pub fn get_credentials(
+        &self,
+        user_id: Option<String>,
+        user_token: Option<String>,
+    ) -> Result<Credentials, Error> {
+        match (
+            user_id.or_else(|| self.user_id.clone()),
+            user_token.or_else(|| self.user_token.clone()),
+        ) {
+            (Some(user_id), Some(user_token)) => Ok(Credentials {
+                user_id,
+                user_token,
+            }),
+            _ => Err(Error::MissingCredentials),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_merges_config() {
+        let config: Config = toml::from_str(
+            r#"
+            user_id = "file-id"
+            user_token = "file-token"
+            records_dir = "records"
+
+            [server]
+            base_url = "http://localhost:8080"
+            timeout = 5.0
+
+            [[accounts]]
+            user_id = "second-id"
+            user_token = "second-token"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.records_dir, Some(PathBuf::from("records")));
+        assert_eq!(config.book, None);
+        assert_eq!(config.accounts[0].user_id, "second-id");
+        let settings = config
+            .server
+            .merge(&ServerConfig {
+                proxy: Some(String::from("http://proxy:3128")),
+                timeout: Some(2.5),
+                ..ServerConfig::default()
+            })
+            .get_settings();
+        assert_eq!(settings.base_url, "http://localhost:8080");
+        assert_eq!(settings.timeout, Some(Duration::from_millis(2500)));
+        assert_eq!(settings.proxy.as_deref(), Some("http://proxy:3128"));
+        assert_eq!(
+            settings.connect_timeout,
+            fetch::Settings::default().connect_timeout
+        );
+        assert_eq!(
+            config
+                .get_credentials(Some(String::from("env-id")), None)
+                .unwrap(),
+            Credentials {
+                user_id: String::from("env-id"),
+                user_token: String::from("file-token"),
+            }
+        );
+        assert!(matches!(
+            Config::default().get_credentials(Some(String::from("env-id")), None),
+            Err(Error::MissingCredentials)
+        ));
+    }
+
+    fn credentials(user_id: &str) -> Credentials {
+        Credentials {
+            user_id: String::from(user_id),
+            user_token: format!("{}-token", user_id),
+        }
+    }
+
+    #[test]
+    fn it_stores_profiles() {
+        let path = std::env::temp_dir().join("five_in_a_row_it_stores_profiles.toml");
+        let _ = fs::remove_file(&path);
+        let mut store = CredentialStore::load_or_default(&path).unwrap();
+        assert_eq!(store.get_default(), None);
+        store.add("first", credentials("id1")).unwrap();
+        store.add("second", credentials("id2")).unwrap();
+        assert!(matches!(
+            store.add("first", credentials("id3")),
+            Err(Error::ProfileExists(_))
+        ));
+        store.save(&path).unwrap();
+
+        let loaded = CredentialStore::load_or_default(&path).unwrap();
+        assert_eq!(loaded, store);
+        assert_eq!(loaded.get_default(), Some(credentials("id1")));
+        assert_eq!(loaded.get("second").unwrap(), credentials("id2"));
+        assert!(matches!(loaded.get("third"), Err(Error::UnknownProfile(_))));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn it_loads_missing_file_as_default() {
+        let path = std::env::temp_dir().join("five_in_a_row_missing_config.toml");
+        assert_eq!(Config::load_or_default(&path).unwrap(), Config::default());
+    }
+}
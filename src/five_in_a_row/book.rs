@@ -0,0 +1,197 @@
+use crate::five_in_a_row::canonical::canonicalize_for_move;
+use crate::five_in_a_row::mv::FiveInRowMove;
+use crate::five_in_a_row::FiveInRow;
+use crate::game::{Book, GameMove};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(_) => write!(f, "IO Error"),
+            Self::Json(e) => write!(f, "Invalid opening book ({})", e),
+        }
+    }
+}
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Json(err) => Some(err),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookMove {
+    pub x: i32,
+    pub y: i32,
+    pub weight: u32,
+}
+
+/// Moves prepared for the opening positions.
+///
+/// Positions which differ only by a rotation, a reflection or a translation
+/// share the same entry.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpeningBook {
+    pub positions: BTreeMap<String, Vec<BookMove>>,
+}
+
+impl OpeningBook {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Adds the weight to the move played by the player on the move
+    pub fn add(&mut self, game: &FiveInRow, mv: &FiveInRowMove, weight: u32) {
+        let (canonical, _) = canonicalize_for_move(game);
+        let (x, y) = canonical.transform.apply(mv.get_x(), mv.get_y());
+        let moves = self.positions.entry(canonical.key).or_default();
+        match moves.iter_mut().find(|m| m.x == x && m.y == y) {
+            Some(book_move) => book_move.weight += weight,
+            None => moves.push(BookMove { x, y, weight }),
+        }
+    }
+
+    /// Book moves of the player on the move with their weights
+    pub fn get_moves(&self, game: &FiveInRow) -> Vec<(FiveInRowMove, u32)> {
+        let (canonical, my_turn) = canonicalize_for_move(game);
+        self.positions
+            .get(&canonical.key)
+            .map_or(Vec::new(), |moves| {
+                moves
+                    .iter()
+                    .filter(|m| m.weight > 0)
+                    .map(|m| {
+                        let (x, y) = canonical.transform.invert(m.x, m.y);
+                        let mv = if my_turn {
+                            FiveInRowMove::Mine(x, y)
+                        } else {
+                            FiveInRowMove::Rivals(x, y)
+                        };
+                        (mv, m.weight)
+                    })
+                    .filter(|(mv, _)| game.get_move_at(mv.get_x(), mv.get_y()).is_none())
+                    .collect()
+            })
+    }
+
+    /// Picks one of the book moves randomly, the probability is proportional
+    /// to the weight of the move
+    pub fn choose<R: Rng>(&self, game: &FiveInRow, rng: &mut R) -> Option<FiveInRowMove> {
+        let moves = self.get_moves(game);
+        let distribution = WeightedIndex::new(moves.iter().map(|(_, w)| *w)).ok()?;
+        Some(moves[distribution.sample(rng)].0)
+    }
+
+    /// Adds the moves of the winner played within the first `max_plies` plies
+    pub fn add_game(&mut self, moves: &[FiveInRowMove], winner_is_mine: bool, max_plies: usize) {
+        let mut game = FiveInRow::create_empty();
+        for mv in moves.iter().take(max_plies) {
+            if mv.is_mine() == winner_is_mine {
+                self.add(&game, mv, 1);
+            }
+            game.moves.push(*mv);
+        }
+    }
+}
+
+impl Book<FiveInRow> for OpeningBook {
+    fn choose_move(&self, game: &FiveInRow) -> Option<FiveInRowMove> {
+        self.choose(game, &mut rand::thread_rng())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn it_finds_equivalent_positions() {
+        let mut book = OpeningBook::default();
+        let game =
+            FiveInRow::from_moves(vec![FiveInRowMove::Mine(0, 0), FiveInRowMove::Rivals(1, 1)]);
+        book.add(&game, &FiveInRowMove::Mine(1, 0), 3);
+        assert_eq!(book.get_moves(&game), vec![(FiveInRowMove::Mine(1, 0), 3)]);
+
+        // rotated by 90 degrees, translated and with swapped players
+        let rotated =
+            FiveInRow::from_moves(vec![FiveInRowMove::Rivals(5, 5), FiveInRowMove::Mine(4, 6)]);
+        assert_eq!(
+            book.get_moves(&rotated),
+            vec![(FiveInRowMove::Rivals(5, 6), 3)]
+        );
+        book.add(&rotated, &FiveInRowMove::Rivals(5, 6), 1);
+        assert_eq!(book.positions.len(), 1);
+        assert_eq!(book.get_moves(&game)[0].1, 4);
+
+        let other =
+            FiveInRow::from_moves(vec![FiveInRowMove::Mine(0, 0), FiveInRowMove::Rivals(2, 0)]);
+        assert!(book.get_moves(&other).is_empty());
+        assert_eq!(book.choose_move(&other), None);
+    }
+
+    #[test]
+    fn it_chooses_weighted_moves() {
+        let mut book = OpeningBook::default();
+        let game = FiveInRow::from_moves(vec![FiveInRowMove::Rivals(0, 0)]);
+        book.add(&game, &FiveInRowMove::Mine(1, 1), 9);
+        book.add(&game, &FiveInRowMove::Mine(1, 0), 1);
+        let mut rng = StdRng::seed_from_u64(1);
+        let diagonal = (0..1000)
+            .filter(|_| book.choose(&game, &mut rng).unwrap().get_y() != 0)
+            .count();
+        assert!(diagonal > 800 && diagonal < 980);
+    }
+
+    #[test]
+    fn it_builds_book_from_game() {
+        let mut book = OpeningBook::default();
+        let moves = [
+            FiveInRowMove::Mine(0, 0),
+            FiveInRowMove::Rivals(1, 1),
+            FiveInRowMove::Mine(1, 0),
+            FiveInRowMove::Rivals(2, 2),
+        ];
+        book.add_game(&moves, true, 4);
+        assert_eq!(book.positions.len(), 2);
+        assert_eq!(
+            book.get_moves(&FiveInRow::create_empty()),
+            vec![(FiveInRowMove::Mine(0, 0), 1)]
+        );
+
+        let path = std::env::temp_dir().join("five_in_a_row_it_builds_book_from_game.json");
+        book.save(&path).unwrap();
+        assert_eq!(OpeningBook::load(&path).unwrap().positions, book.positions);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,223 @@
+use crate::analysis::is_my_turn;
+use crate::five_in_a_row::mv::FiveInRowMove;
+use crate::five_in_a_row::FiveInRow;
+use crate::game::GameMove;
+
+/// Rotations and reflections of the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipX,
+    Transpose,
+    FlipY,
+    AntiTranspose,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::FlipX,
+        Symmetry::Transpose,
+        Symmetry::FlipY,
+        Symmetry::AntiTranspose,
+    ];
+
+    pub fn apply(self, x: i32, y: i32) -> (i32, i32) {
+        match self {
+            Symmetry::Identity => (x, y),
+            Symmetry::Rotate90 => (-y, x),
+            Symmetry::Rotate180 => (-x, -y),
+            Symmetry::Rotate270 => (y, -x),
+            Symmetry::FlipX => (-x, y),
+            Symmetry::Transpose => (y, x),
+            Symmetry::FlipY => (x, -y),
+            Symmetry::AntiTranspose => (-y, -x),
+        }
+    }
+
+    pub fn inverse(self) -> Self {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            s => s,
+        }
+    }
+}
+
+/// Symmetry followed by a translation by `-offset`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transform {
+    pub symmetry: Symmetry,
+    pub offset: (i32, i32),
+}
+
+fn with_coords(mv: &FiveInRowMove, (x, y): (i32, i32)) -> FiveInRowMove {
+    match mv {
+        FiveInRowMove::Mine(_, _) => FiveInRowMove::Mine(x, y),
+        FiveInRowMove::Rivals(_, _) => FiveInRowMove::Rivals(x, y),
+    }
+}
+
+impl Transform {
+    pub fn apply(&self, x: i32, y: i32) -> (i32, i32) {
+        let (x, y) = self.symmetry.apply(x, y);
+        (x - self.offset.0, y - self.offset.1)
+    }
+
+    pub fn invert(&self, x: i32, y: i32) -> (i32, i32) {
+        self.symmetry
+            .inverse()
+            .apply(x + self.offset.0, y + self.offset.1)
+    }
+
+    pub fn apply_move(&self, mv: &FiveInRowMove) -> FiveInRowMove {
+        with_coords(mv, self.apply(mv.get_x(), mv.get_y()))
+    }
+
+    pub fn invert_move(&self, mv: &FiveInRowMove) -> FiveInRowMove {
+        with_coords(mv, self.invert(mv.get_x(), mv.get_y()))
+    }
+}
+
+/// Normalized form of a position shared by all the positions which differ
+/// only by a symmetry and a translation
+#[derive(Debug, Clone)]
+pub struct Canonical {
+    /// The moves transformed into the normalized form, in the original order
+    pub position: FiveInRow,
+    /// Sorted stones of the normalized form, `X` for ours and `O` for rival's
+    pub key: String,
+    /// Transform of the original position into the normalized form
+    pub transform: Transform,
+}
+
+fn get_key(moves: &[FiveInRowMove]) -> String {
+    let mut stones = moves
+        .iter()
+        .map(|mv| (mv.get_x(), mv.get_y(), mv.is_mine()))
+        .collect::<Vec<_>>();
+    stones.sort();
+    stones
+        .iter()
+        .map(|(x, y, mine)| format!("{},{},{}", x, y, if *mine { 'X' } else { 'O' }))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Finds the symmetry with the smallest key, the stones are translated so
+/// that their bounding box starts at (0, 0)
+pub fn canonicalize(game: &FiveInRow) -> Canonical {
+    Symmetry::ALL
+        .iter()
+        .map(|symmetry| {
+            let points = game
+                .moves
+                .iter()
+                .map(|mv| symmetry.apply(mv.get_x(), mv.get_y()))
+                .collect::<Vec<_>>();
+            let transform = Transform {
+                symmetry: *symmetry,
+                offset: (
+                    points.iter().map(|p| p.0).min().unwrap_or(0),
+                    points.iter().map(|p| p.1).min().unwrap_or(0),
+                ),
+            };
+            let moves = game
+                .moves
+                .iter()
+                .map(|mv| transform.apply_move(mv))
+                .collect::<Vec<_>>();
+            Canonical {
+                key: get_key(&moves),
+                position: FiveInRow::from_moves(moves).with_params(game.params.clone()),
+                transform,
+            }
+        })
+        .min_by(|a, b| a.key.cmp(&b.key))
+        .unwrap()
+}
+
+/// The same moves played by the other player
+pub fn swap_players(moves: &[FiveInRowMove]) -> Vec<FiveInRowMove> {
+    moves
+        .iter()
+        .map(|mv| match mv {
+            FiveInRowMove::Mine(x, y) => FiveInRowMove::Rivals(*x, *y),
+            FiveInRowMove::Rivals(x, y) => FiveInRowMove::Mine(*x, *y),
+        })
+        .collect()
+}
+
+/// Canonical form of the position where the stones of the player on the move
+/// are marked as ours, along with whether we are on the move
+pub fn canonicalize_for_move(game: &FiveInRow) -> (Canonical, bool) {
+    let my_turn = is_my_turn(game);
+    if my_turn {
+        return (canonicalize(game), my_turn);
+    }
+    let swapped = FiveInRow::from_moves(swap_players(&game.moves));
+    (canonicalize(&swapped), my_turn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_inverts_transforms() {
+        for symmetry in Symmetry::ALL.iter() {
+            let transform = Transform {
+                symmetry: *symmetry,
+                offset: (3, -1),
+            };
+            let (x, y) = transform.apply(2, -5);
+            assert_eq!(transform.invert(x, y), (2, -5));
+            let mv = FiveInRowMove::Rivals(1, 4);
+            assert_eq!(transform.invert_move(&transform.apply_move(&mv)), mv);
+        }
+    }
+
+    #[test]
+    fn it_canonicalizes_equivalent_positions() {
+        let game = FiveInRow::from_moves(vec![
+            FiveInRowMove::Mine(0, 0),
+            FiveInRowMove::Rivals(1, 1),
+            FiveInRowMove::Mine(1, 0),
+        ]);
+        let canonical = canonicalize(&game);
+        assert_eq!(canonical.position.moves.len(), 3);
+        assert!(canonical.position.moves[0].is_mine());
+
+        for symmetry in Symmetry::ALL.iter() {
+            let moved = FiveInRow::from_moves(
+                game.moves
+                    .iter()
+                    .map(|mv| {
+                        let (x, y) = symmetry.apply(mv.get_x(), mv.get_y());
+                        with_coords(mv, (x + 7, y - 3))
+                    })
+                    .collect(),
+            );
+            let other = canonicalize(&moved);
+            assert_eq!(other.key, canonical.key);
+            for (original, transformed) in moved.moves.iter().zip(other.position.moves.iter()) {
+                assert_eq!(other.transform.invert_move(transformed), *original);
+            }
+        }
+
+        let swapped = FiveInRow::from_moves(swap_players(&game.moves));
+        assert_eq!(swapped.moves[0], FiveInRowMove::Rivals(0, 0));
+        assert_ne!(canonicalize(&swapped).key, canonical.key);
+        // the rival is on the move, its stones are marked as ours
+        let (for_move, my_turn) = canonicalize_for_move(&game);
+        assert!(!my_turn);
+        assert_eq!(for_move.key, canonicalize(&swapped).key);
+        assert_eq!(canonicalize(&FiveInRow::create_empty()).key, "");
+    }
+}
@@ -0,0 +1,257 @@
+use crate::five_in_a_row::mv::FiveInRowMove;
+use crate::five_in_a_row::FiveInRow;
+use crate::game::GameMove;
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    InvalidCharacter(usize, usize, char),
+    UnevenRows(usize),
+    ImpossibleStoneCount(usize, usize),
+    OutOfDiagram(FiveInRowMove),
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidCharacter(row, column, c) => write!(
+                f,
+                "Invalid character '{}' on row {}, column {}",
+                c, row, column
+            ),
+            Self::UnevenRows(row) => write!(f, "Row {} has a different length", row),
+            Self::ImpossibleStoneCount(mine, rivals) => write!(
+                f,
+                "Impossible stone count for the side to move ({} X, {} O)",
+                mine, rivals
+            ),
+            Self::OutOfDiagram(mv) => write!(f, "The move is out of the diagram ({:?})", mv),
+        }
+    }
+}
+impl StdError for Error {}
+
+/// Placement of the diagram on the board
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Origin {
+    /// The center cell of the diagram is the (0, 0) point
+    Center,
+    /// Coordinates of the top left cell of the diagram
+    TopLeft(i32, i32),
+}
+
+#[derive(Debug, Clone)]
+pub struct DiagramOptions {
+    pub origin: Origin,
+    /// We are on the move in the diagrammed position
+    pub my_turn: bool,
+}
+
+impl Default for DiagramOptions {
+    fn default() -> Self {
+        Self {
+            origin: Origin::Center,
+            my_turn: true,
+        }
+    }
+}
+
+/// Parses rows of `X` (ours), `O` (rival's) and `.` (empty) cells, the cells
+/// may be separated by spaces and blank lines are ignored.
+///
+/// A diagram doesn't keep the order of moves, so the stones are interleaved
+/// (row by row from the top) so that the last move belongs to the player who
+/// is not on the move. The stone counts have to allow such an order.
+pub fn parse(input: &str, options: &DiagramOptions) -> Result<FiveInRow, Error> {
+    let rows = input
+        .lines()
+        .map(|l| l.split_whitespace().collect::<String>())
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>();
+    let width = rows.first().map_or(0, |r| r.chars().count()) as i32;
+    let height = rows.len() as i32;
+    let (left, top) = match options.origin {
+        Origin::Center => (-width / 2, height / 2),
+        Origin::TopLeft(x, y) => (x, y),
+    };
+
+    let mut mine = Vec::new();
+    let mut rivals = Vec::new();
+    for (row, line) in rows.iter().enumerate() {
+        if line.chars().count() as i32 != width {
+            return Err(Error::UnevenRows(row + 1));
+        }
+        for (column, c) in line.chars().enumerate() {
+            let (x, y) = (left + column as i32, top - row as i32);
+            match c {
+                'X' | 'x' => mine.push(FiveInRowMove::Mine(x, y)),
+                'O' | 'o' => rivals.push(FiveInRowMove::Rivals(x, y)),
+                '.' | '_' => {}
+                _ => return Err(Error::InvalidCharacter(row + 1, column + 1, c)),
+            }
+        }
+    }
+
+    // the player on the move has either the same number of stones or one less
+    let (on_move, other) = if options.my_turn {
+        (mine, rivals)
+    } else {
+        (rivals, mine)
+    };
+    if other.len() != on_move.len() && other.len() != on_move.len() + 1 {
+        let (mine_count, rivals_count) = if options.my_turn {
+            (on_move.len(), other.len())
+        } else {
+            (other.len(), on_move.len())
+        };
+        return Err(Error::ImpossibleStoneCount(mine_count, rivals_count));
+    }
+    let first_is_other = other.len() > on_move.len();
+    let first = if first_is_other { &other } else { &on_move };
+    let mut second = if first_is_other { &on_move } else { &other }.iter();
+    let mut moves = Vec::new();
+    for mv in first {
+        moves.push(*mv);
+        moves.extend(second.next());
+    }
+    Ok(FiveInRow::from_moves(moves))
+}
+
+/// Serializes the position into rows of `X`, `O` and `.` cells separated
+/// by spaces, `parse` with the same origin restores the position.
+///
+/// The diagram covers all the stones, the `TopLeft` origin has to lie above
+/// and left of them.
+pub fn serialize(game: &FiveInRow, origin: Origin) -> Result<String, Error> {
+    let xs = game.moves.iter().map(|m| m.get_x());
+    let ys = game.moves.iter().map(|m| m.get_y());
+    let (left, top, right, bottom) = match origin {
+        Origin::Center => {
+            let half_width = xs.map(|x| x.abs()).max().unwrap_or(0);
+            let half_height = ys.map(|y| y.abs()).max().unwrap_or(0);
+            (-half_width, half_height, half_width, -half_height)
+        }
+        Origin::TopLeft(left, top) => {
+            let right = xs.max().unwrap_or(left).max(left);
+            let bottom = ys.min().unwrap_or(top).min(top);
+            if let Some(mv) = game
+                .moves
+                .iter()
+                .find(|m| m.get_x() < left || m.get_y() > top)
+            {
+                return Err(Error::OutOfDiagram(*mv));
+            }
+            (left, top, right, bottom)
+        }
+    };
+
+    let mut out = String::new();
+    for y in (bottom..=top).rev() {
+        let row = (left..=right)
+            .map(|x| match game.get_move_at(x, y) {
+                Some(mv) if mv.is_mine() => "X",
+                Some(_) => "O",
+                None => ".",
+            })
+            .collect::<Vec<_>>();
+        out.push_str(&row.join(" "));
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_diagram() {
+        let game = parse(
+            "
+            . X .
+            . X O
+            . O .
+            ",
+            &DiagramOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            game.moves,
+            vec![
+                FiveInRowMove::Mine(0, 1),
+                FiveInRowMove::Rivals(1, 0),
+                FiveInRowMove::Mine(0, 0),
+                FiveInRowMove::Rivals(0, -1),
+            ]
+        );
+
+        let options = DiagramOptions {
+            origin: Origin::TopLeft(10, -3),
+            my_turn: false,
+        };
+        let game = parse("XO\n.X\n", &options).unwrap();
+        assert_eq!(
+            game.moves,
+            vec![
+                FiveInRowMove::Mine(10, -3),
+                FiveInRowMove::Rivals(11, -3),
+                FiveInRowMove::Mine(11, -4),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_diagrams() {
+        let options = DiagramOptions::default();
+        assert_eq!(
+            parse("X.\n.?", &options).unwrap_err(),
+            Error::InvalidCharacter(2, 2, '?')
+        );
+        assert_eq!(
+            parse("X..\nO.", &options).unwrap_err(),
+            Error::UnevenRows(2)
+        );
+        assert_eq!(
+            parse("XXO", &options).unwrap_err(),
+            Error::ImpossibleStoneCount(2, 1)
+        );
+        assert_eq!(
+            parse("XOO\nO..", &options).unwrap_err(),
+            Error::ImpossibleStoneCount(1, 3)
+        );
+        let rivals_turn = DiagramOptions {
+            my_turn: false,
+            ..DiagramOptions::default()
+        };
+        assert!(parse("XXO", &rivals_turn).is_ok());
+    }
+
+    #[test]
+    fn it_round_trips_diagram() {
+        let game = FiveInRow::from_moves(vec![
+            FiveInRowMove::Mine(0, 0),
+            FiveInRowMove::Rivals(1, 1),
+            FiveInRowMove::Mine(-2, 1),
+        ]);
+        let diagram = serialize(&game, Origin::Center).unwrap();
+        assert_eq!(diagram, "X . . O .\n. . X . .\n. . . . .\n");
+        let options = DiagramOptions {
+            origin: Origin::Center,
+            my_turn: false,
+        };
+        let parsed = parse(&diagram, &options).unwrap();
+        assert_eq!(parsed.moves.len(), game.moves.len());
+        for mv in game.moves.iter() {
+            assert_eq!(parsed.get_move_at(mv.get_x(), mv.get_y()), Some(mv));
+        }
+
+        let diagram = serialize(&game, Origin::TopLeft(-2, 1)).unwrap();
+        assert_eq!(diagram, "X . . O\n. . X .\n");
+        let options = DiagramOptions {
+            origin: Origin::TopLeft(-2, 1),
+            my_turn: false,
+        };
+        assert_eq!(parse(&diagram, &options).unwrap().moves.len(), 3);
+        assert!(serialize(&game, Origin::TopLeft(0, 0)).is_err());
+    }
+}
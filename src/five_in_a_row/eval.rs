@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Toml(toml::de::Error),
+    TomlSerialize(toml::ser::Error),
+}
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Self::Toml(e)
+    }
+}
+impl From<toml::ser::Error> for Error {
+    fn from(e: toml::ser::Error) -> Self {
+        Self::TomlSerialize(e)
+    }
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(_) => write!(f, "IO Error"),
+            Self::Json(e) => write!(f, "Invalid evaluation parameters ({})", e),
+            Self::Toml(e) => write!(f, "Invalid evaluation parameters ({})", e),
+            Self::TomlSerialize(e) => write!(f, "Unable to serialize the parameters ({})", e),
+        }
+    }
+}
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Json(err) => Some(err),
+            Self::Toml(err) => Some(err),
+            Self::TomlSerialize(err) => Some(err),
+        }
+    }
+}
+
+/// Weights of the static evaluation of a row of stones.
+///
+/// The row scores are divided by the distance between the outer stones of
+/// the row, so the gaps inside of the row decrease its value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvalParams {
+    /// Five or more stones with gaps between them
+    pub five: f64,
+    pub four: f64,
+    pub three: f64,
+    pub two: f64,
+    /// Multipliers of a row closed by the rival's stone at the distance of
+    /// 1, 2 and 3 points from its end
+    pub closed_gap: [f64; 3],
+    /// Rival's rows count this many times more than ours
+    pub rival_factor: f64,
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        Self {
+            five: 1000.0,
+            four: 220.0,
+            three: 50.0,
+            two: 4.0,
+            closed_gap: [0.5, 0.8, 0.99],
+            rival_factor: 2.5,
+        }
+    }
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "toml")
+}
+
+impl EvalParams {
+    /// Loads the parameters from a `.toml` file or from a json file,
+    /// the missing ones keep their default values
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let content = fs::read_to_string(path)?;
+        if is_toml(path) {
+            Ok(toml::from_str(&content)?)
+        } else {
+            Ok(serde_json::from_str(&content)?)
+        }
+    }
+
+    /// Saves the parameters as toml or json depending on the file extension
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let content = if is_toml(path) {
+            toml::to_string(self)?
+        } else {
+            serde_json::to_string_pretty(self)?
+        };
+        fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_saves_and_loads_params() {
+        let path = std::env::temp_dir().join("five_in_a_row_it_saves_and_loads_params.json");
+        let params = EvalParams {
+            four: 300.0,
+            ..EvalParams::default()
+        };
+        params.save(&path).unwrap();
+        assert_eq!(EvalParams::load(&path).unwrap(), params);
+        fs::remove_file(&path).unwrap();
+
+        let path = path.with_extension("toml");
+        params.save(&path).unwrap();
+        assert_eq!(EvalParams::load(&path).unwrap(), params);
+        fs::remove_file(&path).unwrap();
+
+        // missing weights keep their defaults
+        let partial: EvalParams = serde_json::from_str(r#"{"three": 60.0}"#).unwrap();
+        assert_eq!(partial.three, 60.0);
+        assert_eq!(partial.four, EvalParams::default().four);
+        let partial: EvalParams = toml::from_str("rival_factor = 3.0").unwrap();
+        assert_eq!(partial.rival_factor, 3.0);
+        assert_eq!(partial.closed_gap, EvalParams::default().closed_gap);
+    }
+}
@@ -1,24 +1,58 @@
+#[path = "book.rs"]
+pub mod book;
+
+#[path = "canonical.rs"]
+pub mod canonical;
+
+#[path = "diagram.rs"]
+pub mod diagram;
+
 #[path = "direction.rs"]
 mod dir;
 
+#[path = "eval.rs"]
+pub mod eval;
+
 #[path = "move.rs"]
 pub mod mv;
 
+#[path = "psq.rs"]
+pub mod psq;
+
+#[path = "render.rs"]
+pub mod render;
+
+#[path = "renlib.rs"]
+pub mod renlib;
+
+#[path = "svg.rs"]
+pub mod svg;
+
+#[path = "threats.rs"]
+pub mod threats;
+
 use crate::api::jobs_cz::status::Coordinate;
 use crate::five_in_a_row::dir::Direction;
+use crate::five_in_a_row::eval::EvalParams;
 use crate::five_in_a_row::mv::FiveInRowMove;
 use crate::game::{error::Error, score::Score, Game, GameMove};
+use std::sync::Arc;
 use std::vec::Vec;
 
 #[derive(Debug, Clone)]
 pub struct FiveInRow {
     pub moves: Vec<FiveInRowMove>,
+    /// Weights of the static evaluation, shared by all the searched positions
+    pub params: Arc<EvalParams>,
 }
 
 impl FiveInRow {
     #[allow(dead_code)]
     pub fn create_empty() -> Self {
-        Self { moves: Vec::new() }
+        Self {
+            moves: Vec::new(),
+            params: Arc::default(),
+        }
     }
 
     #[allow(dead_code)]
@@ -33,15 +67,64 @@ impl FiveInRow {
                 }
             })
             .collect();
-        Self { moves }
+        Self {
+            moves,
+            params: Arc::default(),
+        }
     }
 
     #[allow(dead_code)]
     pub fn from_moves(moves: Vec<FiveInRowMove>) -> Self {
-        Self { moves }
+        Self {
+            moves,
+            params: Arc::default(),
+        }
+    }
+
+    pub fn with_params(self, params: Arc<EvalParams>) -> Self {
+        Self { params, ..self }
+    }
+
+    /// Builds the position from a text diagram, see `diagram::parse`
+    pub fn from_diagram(
+        input: &str,
+        options: &diagram::DiagramOptions,
+    ) -> Result<Self, diagram::Error> {
+        diagram::parse(input, options)
+    }
+
+    pub fn to_diagram(&self, origin: diagram::Origin) -> Result<String, diagram::Error> {
+        diagram::serialize(self, origin)
+    }
+
+    pub fn get_move_at(&self, x: i32, y: i32) -> Option<&FiveInRowMove> {
+        self.moves.iter().find(|m| m.get_x() == x && m.get_y() == y)
     }
 
-    fn score_from_row(mv: &FiveInRowMove, vec: &Vec<&FiveInRowMove>) -> Score {
+    /// Returns the stones of the first complete row of five (or more), if any
+    pub fn get_winning_line(&self) -> Option<Vec<FiveInRowMove>> {
+        let steps: [(i32, i32); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+        for mv in self.moves.iter() {
+            for (dx, dy) in steps.iter() {
+                // count each line only from its first stone
+                let previous = self.get_move_at(mv.get_x() - dx, mv.get_y() - dy);
+                if mv.is_same_type(previous) {
+                    continue;
+                }
+                let line: Vec<FiveInRowMove> = (0..)
+                    .map(|i| self.get_move_at(mv.get_x() + i * dx, mv.get_y() + i * dy))
+                    .take_while(|m| mv.is_same_type(*m))
+                    .map(|m| *m.unwrap())
+                    .collect();
+                if line.len() >= 5 {
+                    return Some(line);
+                }
+            }
+        }
+        None
+    }
+
+    fn score_from_row(mv: &FiveInRowMove, vec: &Vec<&FiveInRowMove>, params: &EvalParams) -> Score {
         let mut moves: Vec<&FiveInRowMove> = vec.clone();
         moves.sort();
 
@@ -109,13 +192,13 @@ impl FiveInRow {
                     Score::Loss
                 };
             }
-            score = Score::Numeric(1000.0 / f64::from(total_iter_dist));
+            score = Score::Numeric(params.five / f64::from(total_iter_dist));
         } else if total_iter_cnt >= 4 {
-            score = Score::Numeric(220.0 / f64::from(total_iter_dist));
+            score = Score::Numeric(params.four / f64::from(total_iter_dist));
         } else if total_iter_cnt >= 3 {
-            score = Score::Numeric(50.0 / f64::from(total_iter_dist));
+            score = Score::Numeric(params.three / f64::from(total_iter_dist));
         } else if total_iter_cnt >= 2 {
-            score = Score::Numeric(4.0 / f64::from(total_iter_dist));
+            score = Score::Numeric(params.two / f64::from(total_iter_dist));
         } else {
             score = Score::Numeric(f64::from(total_iter_cnt) / f64::from(total_iter_dist));
         }
@@ -123,38 +206,35 @@ impl FiveInRow {
         if let Some(l_cl) = l_closing {
             let l_gap = l_item.get_distance(l_cl).abs();
             if l_gap <= 1 {
-                score = score * 0.5;
+                score = score * params.closed_gap[0];
             } else if l_gap <= 2 {
-                score = score * 0.8;
+                score = score * params.closed_gap[1];
             } else if l_gap <= 3 {
-                score = score * 0.99;
+                score = score * params.closed_gap[2];
             }
         }
 
         if let Some(r_cl) = r_closing {
             let r_gap = r_item.get_distance(r_cl).abs();
             if r_gap <= 1 {
-                score = score * 0.5;
+                score = score * params.closed_gap[0];
             } else if r_gap <= 2 {
-                score = score * 0.8;
+                score = score * params.closed_gap[1];
             } else if r_gap <= 3 {
-                score = score * 0.99;
+                score = score * params.closed_gap[2];
             }
         }
 
         if mv.is_mine() {
             score
         } else {
-            score * -2.5
+            score * -params.rival_factor
         }
     }
-}
-
-impl Game for FiveInRow {
-    type Move = FiveInRowMove;
 
-    fn get_score(&self) -> Score {
-        let score: Score = self.moves.iter().fold(Score::Numeric(0.0), |res, mv| {
+    /// Static evaluation of the position with the given weights
+    pub fn get_score_with(&self, params: &EvalParams) -> Score {
+        self.moves.iter().fold(Score::Numeric(0.0), |res, mv| {
             res + Direction::create_list_from_move(mv).iter().fold(
                 Score::Numeric(0.0),
                 |res, direction| {
@@ -164,12 +244,19 @@ impl Game for FiveInRow {
                         .filter(|i| direction.is_in_direction(i.get_x(), i.get_y()))
                         .collect::<Vec<_>>();
 
-                    let score = FiveInRow::score_from_row(mv, &items);
+                    let score = FiveInRow::score_from_row(mv, &items, params);
                     res + score
                 },
             )
-        });
-        score
+        })
+    }
+}
+
+impl Game for FiveInRow {
+    type Move = FiveInRowMove;
+
+    fn get_score(&self) -> Score {
+        self.get_score_with(&self.params)
     }
 
     fn do_move(&mut self, new_move: Self::Move) -> Result<(), Error<FiveInRow>> {
@@ -184,6 +271,10 @@ impl Game for FiveInRow {
         Ok(())
     }
 
+    fn get_moves(&self) -> Vec<FiveInRowMove> {
+        self.moves.clone()
+    }
+
     fn get_possible_moves(&self, myself: bool) -> Vec<FiveInRowMove> {
         let mut vec = Vec::new();
         if self.moves.is_empty() {
@@ -213,54 +304,8 @@ impl Game for FiveInRow {
         vec
     }
 
-    fn visualize(&self) {
-        let (min_x, max_x, min_y, max_y) = self.moves.iter().map(|m| (m.get_x(), m.get_y())).fold(
-            (i32::MAX, i32::MIN, i32::MAX, i32::MIN),
-            |(min_x, max_x, min_y, max_y), (x, y)| {
-                (
-                    min_x.min(x - 1),
-                    max_x.max(x + 1),
-                    min_y.min(y - 1),
-                    max_y.max(y + 1),
-                )
-            },
-        );
-        let mut x: i32;
-        let mut y = max_y;
-        x = min_x;
-        while x <= max_x {
-            print!(
-                "  {}{}{} ",
-                if x < 0 { "" } else { " " },
-                x,
-                if x.abs() < 10 { " " } else { "" }
-            );
-            x += 1;
-        }
-        println!();
-        while x <= max_x {
-            print!("┼─────");
-            x += 1;
-        }
-        while y >= min_y {
-            x = min_x;
-            while x <= max_x {
-                let mv = self.moves.iter().find(|m| m.get_x() == x && m.get_y() == y);
-                print!(
-                    "│  {}  ",
-                    mv.map_or(" ", |m| if m.is_mine() { "X" } else { "O" })
-                );
-                x += 1;
-            }
-            println!("│ {}", y);
-            x = min_x;
-            while x <= max_x {
-                print!("┼─────");
-                x += 1;
-            }
-            println!("┼");
-            y -= 1;
-        }
+    fn visualize(&self) -> String {
+        render::render(self, &render::RenderOptions::default())
     }
 }
 
@@ -311,13 +356,15 @@ mod tests {
     fn it_computes_score_for_row() {
         let mv = FiveInRowMove::Mine(0, 0);
         let mvr = FiveInRowMove::Rivals(0, 0);
+        let params = EvalParams::default();
 
-        let score_x = FiveInRow::score_from_row(&mv, &Vec::from([&mv]));
+        let score_x = FiveInRow::score_from_row(&mv, &Vec::from([&mv]), &params);
         let score_xx =
-            FiveInRow::score_from_row(&mv, &Vec::from([&mv, &FiveInRowMove::Mine(0, 1)]));
+            FiveInRow::score_from_row(&mv, &Vec::from([&mv, &FiveInRowMove::Mine(0, 1)]), &params);
         let score_xxx = FiveInRow::score_from_row(
             &mv,
             &Vec::from([&mv, &FiveInRowMove::Mine(0, 1), &FiveInRowMove::Mine(0, 2)]),
+            &params,
         );
         let score_xxxx = FiveInRow::score_from_row(
             &mv,
@@ -327,6 +374,7 @@ mod tests {
                 &FiveInRowMove::Mine(0, 2),
                 &FiveInRowMove::Mine(0, 3),
             ]),
+            &params,
         );
         let score_xxxxx = FiveInRow::score_from_row(
             &mv,
@@ -337,6 +385,7 @@ mod tests {
                 &FiveInRowMove::Mine(0, 3),
                 &FiveInRowMove::Mine(0, 4),
             ]),
+            &params,
         );
         let score_xxxox = FiveInRow::score_from_row(
             &mv,
@@ -347,6 +396,7 @@ mod tests {
                 &FiveInRowMove::Rivals(0, 3),
                 &FiveInRowMove::Mine(0, 4),
             ]),
+            &params,
         );
 
         let score_xxxxex = FiveInRow::score_from_row(
@@ -358,6 +408,7 @@ mod tests {
                 &FiveInRowMove::Mine(0, 3),
                 &FiveInRowMove::Mine(0, 6),
             ]),
+            &params,
         );
 
         let score_ooooo = FiveInRow::score_from_row(
@@ -369,6 +420,7 @@ mod tests {
                 &FiveInRowMove::Rivals(0, 3),
                 &FiveInRowMove::Rivals(0, 4),
             ]),
+            &params,
         );
 
         let score_oxxxxo = FiveInRow::score_from_row(
@@ -381,6 +433,7 @@ mod tests {
                 &FiveInRowMove::Mine(0, 3),
                 &FiveInRowMove::Rivals(0, 4),
             ]),
+            &params,
         );
 
         let score_oxxxxeo = FiveInRow::score_from_row(
@@ -393,6 +446,7 @@ mod tests {
                 &FiveInRowMove::Mine(0, 3),
                 &FiveInRowMove::Rivals(0, 5),
             ]),
+            &params,
         );
 
         assert!(score_oxxxxo < score_x);
@@ -410,6 +464,50 @@ mod tests {
         assert!(score_xxx > score_xxxox);
     }
 
+    #[test]
+    fn it_scores_with_params() {
+        let game = FiveInRow::from_moves(vec![
+            FiveInRowMove::Mine(0, 0),
+            FiveInRowMove::Rivals(1, 1),
+            FiveInRowMove::Mine(0, 1),
+        ]);
+        assert_eq!(
+            game.get_score(),
+            game.get_score_with(&EvalParams::default())
+        );
+        let params = EvalParams {
+            two: 40.0,
+            ..EvalParams::default()
+        };
+        let tuned = game.clone().with_params(Arc::new(params.clone()));
+        assert_eq!(tuned.get_score(), game.get_score_with(&params));
+        assert!(tuned.get_score() > game.get_score());
+        assert_eq!(tuned.params.two, 40.0);
+    }
+
+    #[test]
+    fn it_finds_winning_line() {
+        let mut moves = Vec::from([
+            FiveInRowMove::Mine(0, 0),
+            FiveInRowMove::Rivals(1, 0),
+            FiveInRowMove::Mine(1, 1),
+            FiveInRowMove::Rivals(2, 0),
+            FiveInRowMove::Mine(2, 2),
+            FiveInRowMove::Rivals(3, 0),
+            FiveInRowMove::Mine(3, 3),
+        ]);
+        assert_eq!(
+            FiveInRow::from_moves(moves.clone()).get_winning_line(),
+            None
+        );
+
+        moves.push(FiveInRowMove::Rivals(0, 1));
+        moves.push(FiveInRowMove::Mine(4, 4));
+        let line = FiveInRow::from_moves(moves).get_winning_line().unwrap();
+        assert_eq!(line.len(), 5);
+        assert!(line.iter().all(|m| m.is_mine() && m.get_x() == m.get_y()));
+    }
+
     #[test]
     fn it_replays_game_1() {
         let moves = Vec::from([
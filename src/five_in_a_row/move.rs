@@ -1,8 +1,9 @@
 use crate::api::jobs_cz::status::Coordinate;
 use crate::game::GameMove;
 use core::cmp::Ordering;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum FiveInRowMove {
     Mine(i32, i32),
     Rivals(i32, i32),
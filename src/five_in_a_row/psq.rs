@@ -0,0 +1,255 @@
+use crate::five_in_a_row::mv::FiveInRowMove;
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter, Write};
+use std::fs;
+use std::path::Path;
+
+pub const DEFAULT_SIZE: i32 = 20;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    InvalidHeader(String),
+    InvalidMove(usize, String),
+    OutOfBoard(FiveInRowMove),
+}
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(_) => write!(f, "IO Error"),
+            Self::InvalidHeader(line) => write!(f, "Invalid header ({})", line),
+            Self::InvalidMove(line_no, line) => {
+                write!(f, "Invalid move on line {} ({})", line_no, line)
+            }
+            Self::OutOfBoard(mv) => write!(f, "The move is out of the board ({:?})", mv),
+        }
+    }
+}
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Position of the `(0, 0)` point on the 1-based Piskvork board
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Origin {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Origin {
+    pub fn center(width: i32, height: i32) -> Self {
+        Self {
+            x: width / 2 + 1,
+            y: height / 2 + 1,
+        }
+    }
+
+    pub fn move_at(self, psq_x: i32, psq_y: i32, mine: bool) -> FiveInRowMove {
+        let (x, y) = (psq_x - self.x, self.y - psq_y);
+        if mine {
+            FiveInRowMove::Mine(x, y)
+        } else {
+            FiveInRowMove::Rivals(x, y)
+        }
+    }
+
+    pub fn position_of(self, mv: &FiveInRowMove) -> (i32, i32) {
+        (mv.get_x() + self.x, self.y - mv.get_y())
+    }
+}
+
+/// Game stored in the Piskvork `.psq` file.
+///
+/// The file starts with a `Piskvorky WxH, X:Y, N` header followed by one
+/// `x,y,time` line per move, the coordinates are 1-based with `y` growing
+/// downwards. The first move is played by black.
+#[derive(Debug, Clone)]
+pub struct PsqGame {
+    pub width: i32,
+    pub height: i32,
+    pub moves: Vec<FiveInRowMove>,
+}
+
+fn parse_header(line: &str) -> Option<(i32, i32)> {
+    let size = line.strip_prefix("Piskvorky ")?.split(',').next()?;
+    let mut dims = size.trim().split('x');
+    let width = dims.next()?.trim().parse().ok()?;
+    let height = dims.next()?.trim().parse().ok()?;
+    Some((width, height))
+}
+
+fn parse_move(line: &str) -> Option<(i32, i32)> {
+    let mut parts = line.split(',').map(|p| p.trim().parse::<i32>());
+    let x = parts.next()?.ok()?;
+    let y = parts.next()?.ok()?;
+    if x < 1 || y < 1 {
+        return None;
+    }
+    Some((x, y))
+}
+
+/// Parses the game, coordinates are converted relative to the board center.
+///
+/// `first_mine` tells whether the first (black) move belongs to us.
+pub fn parse(content: &str, first_mine: bool) -> Result<PsqGame, Error> {
+    let mut lines = content.lines();
+    let header = lines.next().unwrap_or_default();
+    let (width, height) =
+        parse_header(header).ok_or_else(|| Error::InvalidHeader(String::from(header)))?;
+    let origin = Origin::center(width, height);
+
+    let mut moves = Vec::new();
+    for (i, line) in lines.enumerate() {
+        // the move list is terminated by `-1` or by the names of the players
+        let (x, y) = match parse_move(line) {
+            Some(coords) => coords,
+            None => break,
+        };
+        if x > width || y > height {
+            return Err(Error::InvalidMove(i + 2, String::from(line)));
+        }
+        moves.push(origin.move_at(x, y, (moves.len() % 2 == 0) == first_mine));
+    }
+    Ok(PsqGame {
+        width,
+        height,
+        moves,
+    })
+}
+
+pub fn load(path: &Path, first_mine: bool) -> Result<PsqGame, Error> {
+    parse(&fs::read_to_string(path)?, first_mine)
+}
+
+/// Finds an origin that fits all the moves on the board.
+///
+/// The board center is preferred, otherwise the moves are centered.
+fn get_origin(moves: &[FiveInRowMove], width: i32, height: i32) -> Result<Origin, Error> {
+    let fits = |origin: &Origin| {
+        moves.iter().all(|mv| {
+            let (x, y) = origin.position_of(mv);
+            x >= 1 && x <= width && y >= 1 && y <= height
+        })
+    };
+    let center = Origin::center(width, height);
+    if fits(&center) {
+        return Ok(center);
+    }
+    let min_x = moves.iter().map(|m| m.get_x()).min().unwrap_or(0);
+    let max_x = moves.iter().map(|m| m.get_x()).max().unwrap_or(0);
+    let min_y = moves.iter().map(|m| m.get_y()).min().unwrap_or(0);
+    let max_y = moves.iter().map(|m| m.get_y()).max().unwrap_or(0);
+    let shifted = Origin {
+        x: center.x - (min_x + max_x) / 2,
+        y: center.y + (min_y + max_y) / 2,
+    };
+    if fits(&shifted) {
+        return Ok(shifted);
+    }
+    let out = moves.iter().find(|mv| {
+        let (x, y) = shifted.position_of(mv);
+        x < 1 || x > width || y < 1 || y > height
+    });
+    Err(Error::OutOfBoard(*out.unwrap_or(&moves[0])))
+}
+
+/// Serializes the moves in the order they were played.
+///
+/// Piskvork boards are bounded, so the moves are translated when they do not
+/// fit the board around its center.
+pub fn to_string(moves: &[FiveInRowMove], width: i32, height: i32) -> Result<String, Error> {
+    let origin = get_origin(moves, width, height)?;
+    let mut out = String::new();
+    let center = Origin::center(width, height);
+    let _ = writeln!(
+        out,
+        "Piskvorky {}x{}, {}:{}, 0",
+        width, height, center.x, center.y
+    );
+    for mv in moves.iter() {
+        let (x, y) = origin.position_of(mv);
+        let _ = writeln!(out, "{},{},0", x, y);
+    }
+    out.push_str("-1\n");
+    Ok(out)
+}
+
+pub fn save(path: &Path, moves: &[FiveInRowMove], width: i32, height: i32) -> Result<(), Error> {
+    fs::write(path, to_string(moves, width, height)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_psq() {
+        let content =
+            "Piskvorky 20x20, 11:11, 0\n11,11,1203\n12,10,0\n10,12,500\n-1\nai1.exe\nai2.exe\n-1\n";
+        let game = parse(content, true).unwrap();
+        assert_eq!((game.width, game.height), (20, 20));
+        assert_eq!(
+            game.moves,
+            vec![
+                FiveInRowMove::Mine(0, 0),
+                FiveInRowMove::Rivals(1, 1),
+                FiveInRowMove::Mine(-1, -1),
+            ]
+        );
+        let game = parse(content, false).unwrap();
+        assert_eq!(game.moves[0], FiveInRowMove::Rivals(0, 0));
+    }
+
+    #[test]
+    fn it_fails_on_invalid_input() {
+        assert!(matches!(
+            parse("Gomoku 20x20\n1,1,0\n", true),
+            Err(Error::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            parse("Piskvorky 15x15, 8:8, 0\n16,1,0\n", true),
+            Err(Error::InvalidMove(2, _))
+        ));
+    }
+
+    #[test]
+    fn it_round_trips_moves() {
+        let moves = vec![
+            FiveInRowMove::Mine(0, 0),
+            FiveInRowMove::Rivals(0, 1),
+            FiveInRowMove::Mine(-3, 5),
+        ];
+        let content = to_string(&moves, DEFAULT_SIZE, DEFAULT_SIZE).unwrap();
+        assert!(content.starts_with("Piskvorky 20x20, 11:11, 0\n11,11,0\n11,10,0\n8,6,0\n"));
+        let game = parse(&content, true).unwrap();
+        assert_eq!(game.moves, moves);
+    }
+
+    #[test]
+    fn it_translates_moves_outside_of_board() {
+        let moves = vec![FiveInRowMove::Mine(12, 0), FiveInRowMove::Rivals(13, 0)];
+        let content = to_string(&moves, 15, 15).unwrap();
+        let game = parse(&content, true).unwrap();
+        assert_eq!(
+            game.moves,
+            vec![FiveInRowMove::Mine(0, 0), FiveInRowMove::Rivals(1, 0)]
+        );
+
+        let moves = vec![FiveInRowMove::Mine(-10, 0), FiveInRowMove::Rivals(10, 0)];
+        assert!(matches!(
+            to_string(&moves, 15, 15),
+            Err(Error::OutOfBoard(_))
+        ));
+    }
+}
@@ -0,0 +1,343 @@
+use crate::five_in_a_row::mv::FiveInRowMove;
+use crate::five_in_a_row::FiveInRow;
+use crate::game::GameMove;
+use std::fmt::Write;
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_MINE: &str = "\x1b[1;31m";
+const ANSI_RIVALS: &str = "\x1b[1;34m";
+const ANSI_LAST: &str = "\x1b[7m";
+const ANSI_WINNING: &str = "\x1b[42m";
+
+/// Half of the standard 15x15 board, the point (0, 0) is its center (H8)
+const STANDARD_HALF_SIZE: i32 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Labels {
+    None,
+    Numeric,
+    Standard,
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    pub colors: bool,
+    pub labels: Labels,
+    pub ascii: bool,
+    pub highlight_last: bool,
+    pub highlight_winning_line: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            colors: false,
+            labels: Labels::Numeric,
+            ascii: false,
+            highlight_last: true,
+            highlight_winning_line: true,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Compact single-line-per-row output without colours, suitable for logs
+    pub fn compact() -> Self {
+        Self {
+            colors: false,
+            labels: Labels::Standard,
+            ascii: true,
+            highlight_last: true,
+            highlight_winning_line: true,
+        }
+    }
+
+    /// Box drawing output with ANSI colours for interactive terminals
+    pub fn terminal() -> Self {
+        Self {
+            colors: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// Column label in the standard A–O notation, the coordinate in parentheses
+/// outside of the 15x15 board
+pub fn column_label(x: i32) -> String {
+    if x.abs() <= STANDARD_HALF_SIZE {
+        let letter = (b'A' + (x + STANDARD_HALF_SIZE) as u8) as char;
+        letter.to_string()
+    } else {
+        format!("({})", x)
+    }
+}
+
+/// Row label in the standard 1–15 notation, the coordinate in parentheses
+/// outside of the 15x15 board
+pub fn row_label(y: i32) -> String {
+    if y.abs() <= STANDARD_HALF_SIZE {
+        (y + STANDARD_HALF_SIZE + 1).to_string()
+    } else {
+        format!("({})", y)
+    }
+}
+
+/// Position of the move in the standard notation, e.g. `H8` for (0, 0)
+pub fn notation(mv: &FiveInRowMove) -> String {
+    format!("{}{}", column_label(mv.get_x()), row_label(mv.get_y()))
+}
+
+/// Parses a position in the standard notation (e.g. `h8` or `(8)(-9)` off
+/// the 15x15 board), the inverse of `notation`
+pub fn parse_notation(input: &str) -> Option<(i32, i32)> {
+    let input = input.trim();
+    let (x, row) = match input.strip_prefix('(') {
+        Some(rest) => {
+            let (x, row) = rest.split_once(')')?;
+            (x.parse().ok()?, row)
+        }
+        None => {
+            let column = input.chars().next()?.to_ascii_uppercase();
+            if !('A'..='O').contains(&column) {
+                return None;
+            }
+            (column as i32 - 'A' as i32 - STANDARD_HALF_SIZE, &input[1..])
+        }
+    };
+    let y = match row.strip_prefix('(') {
+        Some(rest) => rest.strip_suffix(')')?.parse().ok()?,
+        None => {
+            let row: i32 = row.parse().ok()?;
+            if !(1..=2 * STANDARD_HALF_SIZE + 1).contains(&row) {
+                return None;
+            }
+            row - STANDARD_HALF_SIZE - 1
+        }
+    };
+    Some((x, y))
+}
+
+/// Bounding box of the played moves extended by one empty cell on each side
+pub fn get_bounds(game: &FiveInRow) -> (i32, i32, i32, i32) {
+    if game.moves.is_empty() {
+        return (-1, 1, -1, 1);
+    }
+    game.moves.iter().map(|m| (m.get_x(), m.get_y())).fold(
+        (i32::MAX, i32::MIN, i32::MAX, i32::MIN),
+        |(min_x, max_x, min_y, max_y), (x, y)| {
+            (
+                min_x.min(x - 1),
+                max_x.max(x + 1),
+                min_y.min(y - 1),
+                max_y.max(y + 1),
+            )
+        },
+    )
+}
+
+struct Cell {
+    symbol: char,
+    mine: Option<bool>,
+    last: bool,
+    winning: bool,
+}
+
+impl Cell {
+    fn format(&self, options: &RenderOptions) -> String {
+        let marks = if self.winning {
+            ('[', ']')
+        } else if self.last {
+            ('(', ')')
+        } else {
+            (' ', ' ')
+        };
+        if !options.colors {
+            return format!("{}{}{}", marks.0, self.symbol, marks.1);
+        }
+        let mut style = String::new();
+        match self.mine {
+            Some(true) => style.push_str(ANSI_MINE),
+            Some(false) => style.push_str(ANSI_RIVALS),
+            None => {}
+        }
+        if self.winning {
+            style.push_str(ANSI_WINNING);
+        } else if self.last {
+            style.push_str(ANSI_LAST);
+        }
+        if style.is_empty() {
+            format!(" {} ", self.symbol)
+        } else {
+            format!(" {}{}{} ", style, self.symbol, ANSI_RESET)
+        }
+    }
+}
+
+fn get_cell(
+    game: &FiveInRow,
+    options: &RenderOptions,
+    winning: &[FiveInRowMove],
+    x: i32,
+    y: i32,
+) -> Cell {
+    let mv = game.get_move_at(x, y);
+    let last = options.highlight_last
+        && game
+            .moves
+            .last()
+            .is_some_and(|l| l.get_x() == x && l.get_y() == y);
+    let winning =
+        options.highlight_winning_line && winning.iter().any(|w| w.get_x() == x && w.get_y() == y);
+    let empty = if options.ascii { '.' } else { ' ' };
+    Cell {
+        symbol: mv.map_or(empty, |m| if m.is_mine() { 'X' } else { 'O' }),
+        mine: mv.map(|m| m.is_mine()),
+        last,
+        winning,
+    }
+}
+
+fn get_labels(options: &RenderOptions, min: i32, max: i32, column: bool) -> Vec<String> {
+    (min..=max)
+        .map(|i| match options.labels {
+            Labels::None => String::new(),
+            Labels::Numeric => i.to_string(),
+            Labels::Standard => {
+                if column {
+                    column_label(i)
+                } else {
+                    row_label(i)
+                }
+            }
+        })
+        .collect()
+}
+
+/// Renders the game board into a string.
+///
+/// The y axis grows upwards, same as on the jobs.cz board.
+pub fn render(game: &FiveInRow, options: &RenderOptions) -> String {
+    let (min_x, max_x, min_y, max_y) = get_bounds(game);
+    let winning = game.get_winning_line().unwrap_or_default();
+    let column_labels = get_labels(options, min_x, max_x, true);
+    let row_labels = get_labels(options, min_y, max_y, false);
+    let with_labels = options.labels != Labels::None;
+    let mut out = String::new();
+
+    if options.ascii {
+        if with_labels {
+            out.push_str("    ");
+            for label in column_labels.iter() {
+                let _ = write!(out, "{:^3}", label);
+            }
+            out.push('\n');
+        }
+        for (y, label) in (min_y..=max_y).rev().zip(row_labels.iter().rev()) {
+            if with_labels {
+                let _ = write!(out, "{:>4}", label);
+            }
+            for x in min_x..=max_x {
+                out.push_str(&get_cell(game, options, &winning, x, y).format(options));
+            }
+            out.push('\n');
+        }
+        return out;
+    }
+
+    let separator = format!("{}┼\n", "┼─────".repeat(column_labels.len()));
+    if with_labels {
+        for label in column_labels.iter() {
+            let _ = write!(out, " {:^5}", label);
+        }
+        out.push('\n');
+    }
+    out.push_str(&separator);
+    for (y, label) in (min_y..=max_y).rev().zip(row_labels.iter().rev()) {
+        for x in min_x..=max_x {
+            let _ = write!(
+                out,
+                "│ {} ",
+                get_cell(game, options, &winning, x, y).format(options)
+            );
+        }
+        if with_labels {
+            let _ = writeln!(out, "│ {}", label);
+        } else {
+            out.push_str("│\n");
+        }
+        out.push_str(&separator);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_creates_standard_labels() {
+        assert_eq!(column_label(-7), "A");
+        assert_eq!(column_label(0), "H");
+        assert_eq!(column_label(7), "O");
+        assert_eq!(column_label(8), "(8)");
+        assert_eq!(row_label(-7), "1");
+        assert_eq!(row_label(7), "15");
+        assert_eq!(row_label(-8), "(-8)");
+        assert_eq!(notation(&FiveInRowMove::Mine(0, 0)), "H8");
+        assert_eq!(notation(&FiveInRowMove::Mine(8, 0)), "(8)8");
+        assert_eq!(notation(&FiveInRowMove::Mine(0, -8)), "H(-8)");
+    }
+
+    #[test]
+    fn it_parses_notation() {
+        assert_eq!(parse_notation("H8"), Some((0, 0)));
+        assert_eq!(parse_notation("a1"), Some((-7, -7)));
+        assert_eq!(parse_notation("o15"), Some((7, 7)));
+        assert_eq!(parse_notation("p1"), None);
+        assert_eq!(parse_notation("h16"), None);
+        assert_eq!(parse_notation("h"), None);
+        assert_eq!(parse_notation("(8)(-9)"), Some((8, -9)));
+        assert_eq!(parse_notation("(8"), None);
+    }
+
+    #[test]
+    fn it_parses_notation_off_board() {
+        for (x, y) in [(8, 0), (0, -8), (-20, 15), (7, 7), (-7, -7)] {
+            let mv = FiveInRowMove::Mine(x, y);
+            assert_eq!(parse_notation(&notation(&mv)), Some((x, y)));
+        }
+    }
+
+    #[test]
+    fn it_renders_compact_board() {
+        let game =
+            FiveInRow::from_moves(vec![FiveInRowMove::Mine(0, 0), FiveInRowMove::Rivals(1, 0)]);
+        let options = RenderOptions {
+            labels: Labels::None,
+            ..RenderOptions::compact()
+        };
+        let rendered = render(&game, &options);
+        assert_eq!(rendered, " .  .  .  . \n .  X (O) . \n .  .  .  . \n");
+    }
+
+    #[test]
+    fn it_highlights_winning_line() {
+        let game = FiveInRow::from_moves(
+            (0..5)
+                .map(|x| FiveInRowMove::Mine(x, 0))
+                .collect::<Vec<_>>(),
+        );
+        let rendered = render(&game, &RenderOptions::compact());
+        assert_eq!(rendered.matches("[X]").count(), 5);
+        assert!(rendered.contains("  H  I  J  K  L "));
+    }
+
+    #[test]
+    fn it_renders_colors() {
+        let game = FiveInRow::from_moves(vec![FiveInRowMove::Mine(0, 0)]);
+        let rendered = render(&game, &RenderOptions::terminal());
+        assert!(rendered.contains(ANSI_MINE));
+        assert!(rendered.contains(ANSI_RESET));
+        assert!(!render(&game, &RenderOptions::default()).contains(ANSI_RESET));
+    }
+}
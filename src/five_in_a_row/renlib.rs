@@ -0,0 +1,253 @@
+use crate::five_in_a_row::mv::FiveInRowMove;
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+const HEADER: [u8; 20] = [
+    0xFF, b'R', b'e', b'n', b'L', b'i', b'b', 0xFF, 0x03, 0x04, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0xFF, 0xFF, 0xFF, 0xFF,
+];
+/// Renlib libraries use the 15x15 board, (0, 0) is its center
+const HALF_SIZE: i32 = 7;
+
+/// The next node is the first child of this node
+const FLAG_DOWN: u8 = 0x80;
+/// This node has a sibling stored after its subtree
+const FLAG_RIGHT: u8 = 0x40;
+const FLAG_OLD_COMMENT: u8 = 0x20;
+const FLAG_COMMENT: u8 = 0x08;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    InvalidHeader,
+    UnexpectedEnd,
+    OutOfBoard(FiveInRowMove),
+}
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(_) => write!(f, "IO Error"),
+            Self::InvalidHeader => write!(f, "Not a Renlib file"),
+            Self::UnexpectedEnd => write!(f, "Unexpected end of file"),
+            Self::OutOfBoard(mv) => write!(f, "The move is out of the board ({:?})", mv),
+        }
+    }
+}
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+fn to_move(position: u8, mine: bool) -> Option<FiveInRowMove> {
+    if position == 0 {
+        return None;
+    }
+    let column = i32::from(position & 0x0F) - 1;
+    let row = i32::from(position >> 4);
+    let (x, y) = (column - HALF_SIZE, HALF_SIZE - row);
+    Some(if mine {
+        FiveInRowMove::Mine(x, y)
+    } else {
+        FiveInRowMove::Rivals(x, y)
+    })
+}
+
+fn from_move(mv: &FiveInRowMove) -> Result<u8, Error> {
+    let (column, row) = (mv.get_x() + HALF_SIZE, HALF_SIZE - mv.get_y());
+    if !(0..=2 * HALF_SIZE).contains(&column) || !(0..=2 * HALF_SIZE).contains(&row) {
+        return Err(Error::OutOfBoard(*mv));
+    }
+    Ok((row << 4) as u8 | (column + 1) as u8)
+}
+
+/// Skips the zero terminated comment, the text is padded to an even length
+fn skip_comment(data: &[u8], mut i: usize) -> Result<usize, Error> {
+    let start = i;
+    while *data.get(i).ok_or(Error::UnexpectedEnd)? != 0 {
+        i += 1;
+    }
+    i += 1;
+    if (i - start) % 2 == 1 {
+        i += 1;
+    }
+    Ok(i)
+}
+
+/// Parses the opening library into the list of all its lines.
+///
+/// Each line is a sequence of moves from the root of the library to one of
+/// its leaves, the first (black) move belongs to us when `first_mine` is set.
+/// Comments and board marks are skipped.
+pub fn parse(data: &[u8], first_mine: bool) -> Result<Vec<Vec<FiveInRowMove>>, Error> {
+    if data.len() < HEADER.len() || data[0..8] != HEADER[0..8] {
+        return Err(Error::InvalidHeader);
+    }
+    let mut lines = Vec::new();
+    // positions of the moves from the root to the current node, `None` stands for a pass
+    let mut path: Vec<Option<u8>> = Vec::new();
+    // path lengths where the pending siblings are attached
+    let mut stack: Vec<usize> = Vec::new();
+    let mut i = HEADER.len();
+    while i < data.len() {
+        let position = data[i];
+        let flags = *data.get(i + 1).ok_or(Error::UnexpectedEnd)?;
+        i += 2;
+        if flags & (FLAG_COMMENT | FLAG_OLD_COMMENT) != 0 {
+            i = skip_comment(data, i)?;
+        }
+
+        if flags & FLAG_RIGHT != 0 {
+            stack.push(path.len());
+        }
+        path.push(if position == 0 { None } else { Some(position) });
+        if flags & FLAG_DOWN == 0 {
+            let line = path
+                .iter()
+                .enumerate()
+                .filter_map(|(ply, p)| p.and_then(|p| to_move(p, (ply % 2 == 0) == first_mine)))
+                .collect::<Vec<_>>();
+            if !line.is_empty() {
+                lines.push(line);
+            }
+            match stack.pop() {
+                Some(len) => path.truncate(len),
+                None => break,
+            }
+        }
+    }
+    Ok(lines)
+}
+
+pub fn load(path: &Path, first_mine: bool) -> Result<Vec<Vec<FiveInRowMove>>, Error> {
+    parse(&fs::read(path)?, first_mine)
+}
+
+struct Node {
+    position: u8,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn insert(&mut self, positions: &[u8]) {
+        if let Some((first, rest)) = positions.split_first() {
+            let index = match self.children.iter().position(|c| c.position == *first) {
+                Some(index) => index,
+                None => {
+                    self.children.push(Node {
+                        position: *first,
+                        children: Vec::new(),
+                    });
+                    self.children.len() - 1
+                }
+            };
+            self.children[index].insert(rest);
+        }
+    }
+
+    fn write_children(&self, out: &mut Vec<u8>) {
+        for (i, child) in self.children.iter().enumerate() {
+            let mut flags = 0;
+            if !child.children.is_empty() {
+                flags |= FLAG_DOWN;
+            }
+            if i + 1 < self.children.len() {
+                flags |= FLAG_RIGHT;
+            }
+            out.push(child.position);
+            out.push(flags);
+            child.write_children(out);
+        }
+    }
+}
+
+/// Serializes the lines into an opening library, lines sharing the same
+/// beginning are merged into a single tree branch.
+pub fn to_bytes(lines: &[Vec<FiveInRowMove>]) -> Result<Vec<u8>, Error> {
+    let mut root = Node {
+        position: 0,
+        children: Vec::new(),
+    };
+    for line in lines.iter() {
+        let positions = line.iter().map(from_move).collect::<Result<Vec<_>, _>>()?;
+        root.insert(&positions);
+    }
+    let mut out = HEADER.to_vec();
+    root.write_children(&mut out);
+    Ok(out)
+}
+
+pub fn save(path: &Path, lines: &[Vec<FiveInRowMove>]) -> Result<(), Error> {
+    fs::write(path, to_bytes(lines)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_converts_positions() {
+        assert_eq!(from_move(&FiveInRowMove::Mine(0, 0)).unwrap(), 0x78);
+        assert_eq!(from_move(&FiveInRowMove::Mine(-7, 7)).unwrap(), 0x01);
+        assert_eq!(from_move(&FiveInRowMove::Mine(7, -7)).unwrap(), 0xEF);
+        assert!(from_move(&FiveInRowMove::Mine(8, 0)).is_err());
+        assert_eq!(to_move(0x78, false), Some(FiveInRowMove::Rivals(0, 0)));
+        assert_eq!(to_move(0, true), None);
+    }
+
+    #[test]
+    fn it_parses_tree() {
+        let mut data = HEADER.to_vec();
+        data.extend_from_slice(&[0x78, FLAG_DOWN]); // H8
+        data.extend_from_slice(&[0x68, FLAG_DOWN | FLAG_RIGHT]); // H9
+        data.extend_from_slice(&[0x69, FLAG_COMMENT]); // I9
+        data.extend_from_slice(&[b'o', b'k', 0, 0]); // padded comment
+        data.extend_from_slice(&[0x79, 0]); // I8
+        let lines = parse(&data, true).unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                vec![
+                    FiveInRowMove::Mine(0, 0),
+                    FiveInRowMove::Rivals(0, 1),
+                    FiveInRowMove::Mine(1, 1),
+                ],
+                vec![FiveInRowMove::Mine(0, 0), FiveInRowMove::Rivals(1, 0)],
+            ]
+        );
+    }
+
+    #[test]
+    fn it_round_trips_lines() {
+        let lines = vec![
+            vec![
+                FiveInRowMove::Mine(0, 0),
+                FiveInRowMove::Rivals(1, 1),
+                FiveInRowMove::Mine(2, 0),
+            ],
+            vec![
+                FiveInRowMove::Mine(0, 0),
+                FiveInRowMove::Rivals(1, 1),
+                FiveInRowMove::Mine(-1, 2),
+            ],
+            vec![FiveInRowMove::Mine(0, 0), FiveInRowMove::Rivals(0, -1)],
+        ];
+        let data = to_bytes(&lines).unwrap();
+        assert_eq!(parse(&data, true).unwrap(), lines);
+        assert!(matches!(
+            parse(&data[0..5], true),
+            Err(Error::InvalidHeader)
+        ));
+    }
+}
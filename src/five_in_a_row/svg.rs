@@ -0,0 +1,322 @@
+use crate::five_in_a_row::render::{self, Labels};
+use crate::five_in_a_row::FiveInRow;
+use crate::game::GameMove;
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter, Write};
+use std::fs;
+use std::path::Path;
+
+const COLOR_GRID: &str = "#c8c8c8";
+const COLOR_LABEL: &str = "#808080";
+const COLOR_MINE: &str = "#d62828";
+const COLOR_RIVALS: &str = "#1d4e89";
+const COLOR_LAST: &str = "#fff3b0";
+const COLOR_WINNING: &str = "#2a9d55";
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    #[cfg(feature = "png")]
+    Svg(resvg::usvg::Error),
+    #[cfg(feature = "png")]
+    Png(String),
+}
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+#[cfg(feature = "png")]
+impl From<resvg::usvg::Error> for Error {
+    fn from(e: resvg::usvg::Error) -> Self {
+        Self::Svg(e)
+    }
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(_) => write!(f, "IO Error"),
+            #[cfg(feature = "png")]
+            Self::Svg(_) => write!(f, "SVG Error"),
+            #[cfg(feature = "png")]
+            Self::Png(msg) => write!(f, "PNG Error ({})", msg),
+        }
+    }
+}
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            #[cfg(feature = "png")]
+            Self::Svg(err) => Some(err),
+            #[cfg(feature = "png")]
+            Self::Png(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SvgOptions {
+    /// Size of a single board cell in pixels
+    pub cell_size: u32,
+    pub labels: Labels,
+    /// Write the move order number into every occupied cell
+    pub numbering: bool,
+    pub highlight_last: bool,
+    pub highlight_winning_line: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            cell_size: 32,
+            labels: Labels::Standard,
+            numbering: false,
+            highlight_last: true,
+            highlight_winning_line: true,
+        }
+    }
+}
+
+fn get_label(options: &SvgOptions, i: i32, column: bool) -> String {
+    match options.labels {
+        Labels::None => String::new(),
+        Labels::Numeric => i.to_string(),
+        Labels::Standard => {
+            if column {
+                render::column_label(i)
+            } else {
+                render::row_label(i)
+            }
+        }
+    }
+}
+
+/// Renders the game board as an SVG document.
+///
+/// Moves are drawn in the order of `game.moves`, so the numbering matches the
+/// order in which the moves were played.
+pub fn render_svg(game: &FiveInRow, options: &SvgOptions) -> String {
+    let (min_x, max_x, min_y, max_y) = render::get_bounds(game);
+    let cell = options.cell_size as i32;
+    let margin = if options.labels == Labels::None {
+        cell / 2
+    } else {
+        cell
+    };
+    let columns = max_x - min_x + 1;
+    let rows = max_y - min_y + 1;
+    let width = 2 * margin + columns * cell;
+    let height = 2 * margin + rows * cell;
+    // top left corner of the cell
+    let cell_x = |x: i32| margin + (x - min_x) * cell;
+    let cell_y = |y: i32| margin + (max_y - y) * cell;
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(
+        out,
+        r#"<rect x="0" y="0" width="{}" height="{}" fill="white"/>"#,
+        width, height
+    );
+
+    if options.highlight_last {
+        if let Some(last) = game.moves.last() {
+            let _ = writeln!(
+                out,
+                r#"<rect x="{}" y="{}" width="{c}" height="{c}" fill="{}"/>"#,
+                cell_x(last.get_x()),
+                cell_y(last.get_y()),
+                COLOR_LAST,
+                c = cell
+            );
+        }
+    }
+
+    for i in 0..=columns {
+        let x = margin + i * cell;
+        let _ = writeln!(
+            out,
+            r#"<line x1="{x}" y1="{}" x2="{x}" y2="{}" stroke="{}" stroke-width="1"/>"#,
+            margin,
+            height - margin,
+            COLOR_GRID,
+            x = x
+        );
+    }
+    for i in 0..=rows {
+        let y = margin + i * cell;
+        let _ = writeln!(
+            out,
+            r#"<line x1="{}" y1="{y}" x2="{}" y2="{y}" stroke="{}" stroke-width="1"/>"#,
+            margin,
+            width - margin,
+            COLOR_GRID,
+            y = y
+        );
+    }
+
+    if options.labels != Labels::None {
+        let font_size = cell * 2 / 5;
+        for x in min_x..=max_x {
+            let _ = writeln!(
+                out,
+                r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" text-anchor="middle" fill="{}">{}</text>"#,
+                cell_x(x) + cell / 2,
+                margin - cell / 3,
+                font_size,
+                COLOR_LABEL,
+                get_label(options, x, true)
+            );
+        }
+        for y in min_y..=max_y {
+            let _ = writeln!(
+                out,
+                r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" text-anchor="end" fill="{}">{}</text>"#,
+                margin - cell / 6,
+                cell_y(y) + cell / 2 + font_size / 3,
+                font_size,
+                COLOR_LABEL,
+                get_label(options, y, false)
+            );
+        }
+    }
+
+    let pad = cell / 5;
+    for (i, mv) in game.moves.iter().enumerate() {
+        let (x, y) = (cell_x(mv.get_x()), cell_y(mv.get_y()));
+        if mv.is_mine() {
+            let _ = writeln!(
+                out,
+                r#"<path d="M{} {} L{} {} M{} {} L{} {}" stroke="{}" stroke-width="{}" stroke-linecap="round"/>"#,
+                x + pad,
+                y + pad,
+                x + cell - pad,
+                y + cell - pad,
+                x + cell - pad,
+                y + pad,
+                x + pad,
+                y + cell - pad,
+                COLOR_MINE,
+                cell / 10 + 1
+            );
+        } else {
+            let _ = writeln!(
+                out,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+                x + cell / 2,
+                y + cell / 2,
+                cell / 2 - pad,
+                COLOR_RIVALS,
+                cell / 10 + 1
+            );
+        }
+        if options.numbering {
+            let _ = writeln!(
+                out,
+                r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" fill="black">{}</text>"#,
+                x + 2,
+                y + cell / 4 + 1,
+                cell / 4,
+                i + 1
+            );
+        }
+    }
+
+    if options.highlight_winning_line {
+        if let Some(line) = game.get_winning_line() {
+            if let (Some(first), Some(last)) = (line.first(), line.last()) {
+                let _ = writeln!(
+                    out,
+                    r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" stroke-linecap="round" opacity="0.7"/>"#,
+                    cell_x(first.get_x()) + cell / 2,
+                    cell_y(first.get_y()) + cell / 2,
+                    cell_x(last.get_x()) + cell / 2,
+                    cell_y(last.get_y()) + cell / 2,
+                    COLOR_WINNING,
+                    cell / 6 + 1
+                );
+            }
+        }
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
+
+pub fn save_svg(game: &FiveInRow, options: &SvgOptions, path: &Path) -> Result<(), Error> {
+    fs::write(path, render_svg(game, options))?;
+    Ok(())
+}
+
+/// Rasterises the SVG diagram into a PNG file, labels use the system fonts
+#[cfg(feature = "png")]
+pub fn save_png(game: &FiveInRow, options: &SvgOptions, path: &Path) -> Result<(), Error> {
+    use resvg::{tiny_skia, usvg};
+
+    let mut opt = usvg::Options::default();
+    opt.fontdb_mut().load_system_fonts();
+    let tree = usvg::Tree::from_str(&render_svg(game, options), &opt)?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| Error::Png(String::from("Invalid image size")))?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap
+        .save_png(path)
+        .map_err(|e| Error::Png(e.to_string()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::five_in_a_row::mv::FiveInRowMove;
+
+    #[test]
+    fn it_renders_svg() {
+        let game =
+            FiveInRow::from_moves(vec![FiveInRowMove::Mine(0, 0), FiveInRowMove::Rivals(1, 0)]);
+        let svg = render_svg(&game, &SvgOptions::default());
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<path").count(), 1);
+        assert_eq!(svg.matches("<circle").count(), 1);
+        assert!(svg.contains(">H</text>"));
+        assert!(svg.contains(COLOR_LAST));
+    }
+
+    #[test]
+    fn it_numbers_moves() {
+        let game = FiveInRow::from_moves(
+            (0..5)
+                .flat_map(|i| vec![FiveInRowMove::Mine(i, 0), FiveInRowMove::Rivals(i, 1)])
+                .take(9)
+                .collect::<Vec<_>>(),
+        );
+        let options = SvgOptions {
+            numbering: true,
+            labels: Labels::None,
+            ..SvgOptions::default()
+        };
+        let svg = render_svg(&game, &options);
+        assert!(svg.contains(">9</text>"));
+        assert!(!svg.contains(">10</text>"));
+        assert!(svg.contains(COLOR_WINNING));
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn it_saves_png() {
+        let game = FiveInRow::from_moves(vec![FiveInRowMove::Mine(0, 0)]);
+        let path = std::env::temp_dir().join("five_in_a_row_it_saves_png.png");
+        save_png(&game, &SvgOptions::default(), &path).unwrap();
+        let data = fs::read(&path).unwrap();
+        assert_eq!(&data[1..4], b"PNG");
+        fs::remove_file(&path).unwrap();
+    }
+}
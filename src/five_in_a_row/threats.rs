@@ -0,0 +1,187 @@
+use crate::five_in_a_row::FiveInRow;
+use crate::game::GameMove;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+const DIRECTIONS: [(i32, i32); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ThreatKind {
+    Five,
+    OpenFour,
+    Four,
+    OpenThree,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Threat {
+    pub kind: ThreatKind,
+    pub mine: bool,
+    /// Stones forming the threat
+    pub stones: Vec<(i32, i32)>,
+    /// Empty points which complete the threat, i.e. make five from a four or
+    /// an open four from an open three
+    pub gains: Vec<(i32, i32)>,
+}
+
+type ThreatKey = (bool, usize, Vec<(i32, i32)>);
+
+/// Finds fives, fours and open threes of both players.
+///
+/// The threats are sorted from the most severe one.
+pub fn find_threats(game: &FiveInRow) -> Vec<Threat> {
+    let board: HashMap<(i32, i32), bool> = game
+        .moves
+        .iter()
+        .map(|m| ((m.get_x(), m.get_y()), m.is_mine()))
+        .collect();
+
+    let mut fives: BTreeSet<ThreatKey> = BTreeSet::new();
+    let mut fours: BTreeMap<ThreatKey, BTreeSet<(i32, i32)>> = BTreeMap::new();
+    let mut threes: BTreeMap<ThreatKey, BTreeSet<(i32, i32)>> = BTreeMap::new();
+
+    for mv in game.moves.iter() {
+        let mine = mv.is_mine();
+        for (dir, (dx, dy)) in DIRECTIONS.iter().enumerate() {
+            let cell = |i: i32| (mv.get_x() + i * dx, mv.get_y() + i * dy);
+
+            // windows of five points containing the stone
+            for start in -4..=0 {
+                let window = (start..start + 5).map(cell).collect::<Vec<_>>();
+                if window.iter().any(|p| board.get(p) == Some(&!mine)) {
+                    continue;
+                }
+                let stones = window
+                    .iter()
+                    .filter(|p| board.contains_key(p))
+                    .copied()
+                    .collect::<Vec<_>>();
+                let empty = window
+                    .iter()
+                    .filter(|p| !board.contains_key(p))
+                    .copied()
+                    .collect::<Vec<_>>();
+                match stones.len() {
+                    5 => {
+                        fives.insert((mine, dir, stones));
+                    }
+                    4 => {
+                        fours.entry((mine, dir, stones)).or_default().extend(empty);
+                    }
+                    _ => {}
+                }
+            }
+
+            // windows of six points with empty ends and three stones inside
+            for start in -4..=-1 {
+                let window = (start..start + 6).map(cell).collect::<Vec<_>>();
+                if board.contains_key(&window[0]) || board.contains_key(&window[5]) {
+                    continue;
+                }
+                let inner = &window[1..5];
+                if inner.iter().any(|p| board.get(p) == Some(&!mine)) {
+                    continue;
+                }
+                let stones = inner
+                    .iter()
+                    .filter(|p| board.contains_key(p))
+                    .copied()
+                    .collect::<Vec<_>>();
+                if stones.len() == 3 {
+                    let gain = inner.iter().find(|p| !board.contains_key(p)).copied();
+                    threes.entry((mine, dir, stones)).or_default().extend(gain);
+                }
+            }
+        }
+    }
+
+    let mut threats = Vec::new();
+    for (mine, _, stones) in fives.into_iter() {
+        threats.push(Threat {
+            kind: ThreatKind::Five,
+            mine,
+            stones,
+            gains: Vec::new(),
+        });
+    }
+    for ((mine, _, stones), gains) in fours.into_iter() {
+        threats.push(Threat {
+            kind: if gains.len() > 1 {
+                ThreatKind::OpenFour
+            } else {
+                ThreatKind::Four
+            },
+            mine,
+            stones,
+            gains: gains.into_iter().collect(),
+        });
+    }
+    for ((mine, _, stones), gains) in threes.into_iter() {
+        threats.push(Threat {
+            kind: ThreatKind::OpenThree,
+            mine,
+            stones,
+            gains: gains.into_iter().collect(),
+        });
+    }
+    threats.sort_by_key(|t| (t.kind, !t.mine));
+    threats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::five_in_a_row::mv::FiveInRowMove;
+
+    #[test]
+    fn it_finds_no_threats() {
+        let game = FiveInRow::from_moves(vec![
+            FiveInRowMove::Mine(0, 0),
+            FiveInRowMove::Rivals(1, 0),
+            FiveInRowMove::Mine(0, 1),
+        ]);
+        assert!(find_threats(&game).is_empty());
+    }
+
+    #[test]
+    fn it_finds_open_three_and_four() {
+        let game = FiveInRow::from_moves(vec![
+            FiveInRowMove::Mine(0, 0),
+            FiveInRowMove::Rivals(0, 1),
+            FiveInRowMove::Mine(1, 0),
+            FiveInRowMove::Rivals(1, 1),
+            FiveInRowMove::Mine(2, 0),
+            FiveInRowMove::Rivals(2, 1),
+            FiveInRowMove::Mine(3, 0),
+        ]);
+        let threats = find_threats(&game);
+        assert_eq!(threats.len(), 2);
+        assert_eq!(threats[0].kind, ThreatKind::OpenFour);
+        assert!(threats[0].mine);
+        assert_eq!(threats[0].gains, vec![(-1, 0), (4, 0)]);
+        assert_eq!(threats[1].kind, ThreatKind::OpenThree);
+        assert!(!threats[1].mine);
+        assert_eq!(threats[1].stones, vec![(0, 1), (1, 1), (2, 1)]);
+        assert_eq!(threats[1].gains, vec![(-1, 1), (3, 1)]);
+    }
+
+    #[test]
+    fn it_finds_broken_four_and_five() {
+        let game = FiveInRow::from_moves(vec![
+            FiveInRowMove::Mine(0, 0),
+            FiveInRowMove::Mine(1, 1),
+            FiveInRowMove::Mine(3, 3),
+            FiveInRowMove::Mine(4, 4),
+            FiveInRowMove::Rivals(0, 5),
+            FiveInRowMove::Rivals(1, 5),
+            FiveInRowMove::Rivals(2, 5),
+            FiveInRowMove::Rivals(3, 5),
+            FiveInRowMove::Rivals(4, 5),
+        ]);
+        let threats = find_threats(&game);
+        assert_eq!(threats[0].kind, ThreatKind::Five);
+        assert!(!threats[0].mine);
+        let four = threats.iter().find(|t| t.mine).unwrap();
+        assert_eq!(four.kind, ThreatKind::Four);
+        assert_eq!(four.gains, vec![(2, 2)]);
+    }
+}
@@ -14,6 +14,11 @@ pub enum Error<G: Game + Debug> {
     ApiInvalidData,
     FinishedUnexpectedly,
     Invalid,
+    /// The local board differs from the server
+    Desync {
+        local: usize,
+        server: usize,
+    },
 }
 
 impl<G: Game + Debug> From<fetch::Error> for Error<G> {
@@ -39,6 +44,11 @@ impl<G: Game + Debug> Display for Error<G> {
             Self::ApiInvalidData => write!(f, "Api invalid data"),
             Self::FinishedUnexpectedly => write!(f, "Finished unexpectedly"),
             Self::Invalid => write!(f, "Invalid request or auth"),
+            Self::Desync { local, server } => write!(
+                f,
+                "The board differs from the server ({} local, {} server moves)",
+                local, server
+            ),
         }
     }
 }
@@ -1,8 +1,13 @@
 #[path = "error.rs"]
 pub mod error;
+#[path = "log.rs"]
+pub mod log;
+#[path = "record.rs"]
+pub mod record;
 #[path = "score.rs"]
 pub mod score;
 use crate::game::score::Score;
+use serde::{de::DeserializeOwned, Serialize};
 use std::fmt::Debug;
 
 pub trait GameMove {
@@ -10,10 +15,18 @@ pub trait GameMove {
 }
 
 pub trait Game: Clone + Debug {
-    type Move: GameMove + Eq + Ord + PartialEq + Copy + Debug;
+    type Move: GameMove + Eq + Ord + PartialEq + Copy + Debug + Serialize + DeserializeOwned;
 
     fn get_score(&self) -> Score;
     fn do_move(&mut self, mv: Self::Move) -> Result<(), error::Error<Self>>;
     fn get_possible_moves(&self, myself: bool) -> Vec<Self::Move>;
-    fn visualize(&self);
+    /// Moves played so far in the order they were played
+    fn get_moves(&self) -> Vec<Self::Move>;
+    fn visualize(&self) -> String;
+}
+
+/// Prepared moves consulted before the search
+pub trait Book<G: Game>: Send + Sync {
+    /// Move of the player on the move, `None` when the position is not in the book
+    fn choose_move(&self, game: &G) -> Option<G::Move>;
 }
@@ -1,15 +1,49 @@
 #[path = "suggestion.rs"]
 pub mod suggestion;
 
-use crate::api::game_connection::GameConnection;
-use crate::game::{error::Error, score::Score, Game};
+use crate::api::game_connection::{GameConnection, GameInfo};
+use crate::game::log::GameLog;
+use crate::game::record::{self, GameRecord, GameRecorder};
+use crate::game::{error::Error, score::Score, Book, Game};
 use crate::gameplay::suggestion::Suggestion;
 use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::runtime::{Handle, RuntimeFlavor};
+use tokio::sync::Semaphore;
+
+pub const DEFAULT_SEARCH_DEPTH: u8 = 6;
+/// Number of times the board may be reloaded from the server in one game
+const MAX_RESYNCS: usize = 3;
+
+/// Amount of the game progress printed while playing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Nothing but the results
+    Quiet,
+    /// Moves and the board after each of them
+    Normal,
+    /// Also the search depth, score and number of evaluated positions
+    Verbose,
+}
 
 pub struct GamePlay<G: Game, C: GameConnection<G>> {
     pub game: G,
     pub suggestions: Vec<Suggestion<G>>,
     pub connection: Option<C>,
+    pub search_depth: u8,
+    pub recorder: Option<GameRecorder>,
+    pub record: Option<GameRecord<G::Move>>,
+    /// Number of positions evaluated by the search so far
+    pub nodes: u64,
+    pub book: Option<Box<dyn Book<G>>>,
+    /// Time limit of the search for one move, the search then deepens
+    /// iteratively up to `search_depth`
+    pub move_time: Option<Duration>,
+    pub verbosity: Verbosity,
+    pub log: GameLog,
+    /// Permits of the concurrently running searches shared by the games
+    pub cpu: Option<Arc<Semaphore>>,
 }
 
 impl<G: Game, C: GameConnection<G>> GamePlay<G, C> {
@@ -19,6 +53,15 @@ impl<G: Game, C: GameConnection<G>> GamePlay<G, C> {
             game,
             suggestions: Vec::new(),
             connection: None,
+            search_depth: DEFAULT_SEARCH_DEPTH,
+            recorder: None,
+            record: None,
+            nodes: 0,
+            book: None,
+            move_time: None,
+            verbosity: Verbosity::Normal,
+            log: GameLog::new(),
+            cpu: None,
         }
     }
 
@@ -28,11 +71,20 @@ impl<G: Game, C: GameConnection<G>> GamePlay<G, C> {
             game,
             suggestions: Vec::new(),
             connection: Some(api),
+            search_depth: DEFAULT_SEARCH_DEPTH,
+            recorder: None,
+            record: None,
+            nodes: 0,
+            book: None,
+            move_time: None,
+            verbosity: Verbosity::Normal,
+            log: GameLog::new(),
+            cpu: None,
         })
     }
 
     fn get_single_level_suggestions(
-        &self,
+        &mut self,
         myself: bool,
         parent_moves: &VecDeque<G::Move>,
         count: usize,
@@ -57,6 +109,7 @@ impl<G: Game, C: GameConnection<G>> GamePlay<G, C> {
                 }
             })
             .collect::<Vec<_>>();
+        self.nodes += possibilities.len() as u64;
 
         let (min_score, max_score) = possibilities.iter().fold(
             if myself {
@@ -77,9 +130,9 @@ impl<G: Game, C: GameConnection<G>> GamePlay<G, C> {
 
         possibilities.sort_by(|a, b| {
             if myself {
-                b.get_score().cmp(a.get_score())
+                b.get_score().total_cmp(a.get_score())
             } else {
-                a.get_score().cmp(b.get_score())
+                a.get_score().total_cmp(b.get_score())
             }
         });
         let mut suggestions = possibilities
@@ -126,9 +179,9 @@ impl<G: Game, C: GameConnection<G>> GamePlay<G, C> {
             let sc_a = a.get_deep_score();
             let sc_b = b.get_deep_score();
             if myself {
-                sc_b.cmp(&sc_a)
+                sc_b.total_cmp(&sc_a)
             } else {
-                sc_a.cmp(&sc_b)
+                sc_a.total_cmp(&sc_b)
             }
         });
         Ok(suggestions)
@@ -168,33 +221,173 @@ impl<G: Game, C: GameConnection<G>> GamePlay<G, C> {
         self.suggestions = maybe_suggestion.map_or(Vec::new(), |s| (*s).get_suggestions().clone());
         let res = self.game.do_move(mv);
 
-        Game::visualize(&self.game);
+        if self.verbosity >= Verbosity::Normal {
+            self.log.print(&Game::visualize(&self.game));
+        }
         res
     }
 
+    /// Computes the suggestions for our next move and returns the reached
+    /// depth. With `move_time` set, the depth is increased while the next
+    /// level is expected to finish in time.
+    pub fn search(&mut self) -> Result<u8, Error<G>> {
+        let move_time = match self.move_time {
+            Some(move_time) => move_time,
+            None => {
+                self.compute_suggestions(true, VecDeque::new(), self.search_depth)?;
+                return Ok(self.search_depth);
+            }
+        };
+        let started = Instant::now();
+        let mut depth = 0;
+        loop {
+            let level_started = Instant::now();
+            self.compute_suggestions(true, VecDeque::new(), depth)?;
+            // each level takes a few times longer than the previous one
+            let estimate = started.elapsed() + level_started.elapsed() * 4;
+            if depth >= self.search_depth || estimate > move_time {
+                return Ok(depth);
+            }
+            depth += 1;
+        }
+    }
+
+    /// Searches once a permit of the shared cpu budget is available, other
+    /// tasks of a multi-threaded runtime keep running meanwhile
+    async fn search_with_permit(&mut self) -> Result<u8, Error<G>> {
+        let _permit = match self.cpu.clone() {
+            Some(cpu) => Some(cpu.acquire_owned().await.map_err(|_| Error::Invalid)?),
+            None => None,
+        };
+        match Handle::current().runtime_flavor() {
+            RuntimeFlavor::MultiThread => tokio::task::block_in_place(|| self.search()),
+            _ => self.search(),
+        }
+    }
+
+    fn record_move(&mut self, mv: G::Move, score: Option<Score>, depth: Option<u8>) {
+        if let Some(record) = self.record.as_mut() {
+            record.add_move(mv, score, depth);
+        }
+    }
+
+    fn finish_record(&mut self, result: &Result<String, Error<G>>) {
+        let info = self.connection.as_ref().map(|c| c.get_game_info());
+        if let Some(record) = self.record.as_mut() {
+            record.finished_at = Some(record::now());
+            if let Some(info) = info {
+                record.info = info;
+            }
+            match result {
+                Ok(winner) => record.winner_id = Some(winner.clone()),
+                Err(e) => record.error = Some(e.to_string()),
+            }
+            if let Some(recorder) = self.recorder.as_ref() {
+                match recorder.save(record) {
+                    Ok(path) => {
+                        if self.verbosity >= Verbosity::Normal {
+                            self.log
+                                .print(&format!("Game record saved to {}", path.display()));
+                        }
+                    }
+                    Err(e) => self
+                        .log
+                        .print(&format!("Unable to save the game record: {}", e)),
+                }
+            }
+        }
+    }
+
     pub async fn play(&mut self) -> Result<String, Error<G>> {
+        let info = self
+            .connection
+            .as_ref()
+            .map_or(GameInfo::default(), |c| c.get_game_info());
+        // a resumed game or a game opened by the rival has moves already
+        let mut record = GameRecord::new(info);
+        for mv in self.game.get_moves() {
+            record.add_move(mv, None, None);
+        }
+        self.record = Some(record);
+        let result = self.play_moves().await;
+        self.finish_record(&result);
+        result
+    }
+
+    async fn play_moves(&mut self) -> Result<String, Error<G>> {
+        let mut resyncs = 0;
         let result = loop {
             let (maybe_rivals_move, maybe_winner) = {
                 let connection = self.connection.as_mut().ok_or(Error::Invalid)?;
-                connection.await_move().await?
+                match connection.await_move().await {
+                    Err(Error::Desync { local, server }) if resyncs < MAX_RESYNCS => {
+                        resyncs += 1;
+                        let game = connection
+                            .get_server_game(&self.game)
+                            .await?
+                            .ok_or(Error::Desync { local, server })?;
+                        if self.verbosity >= Verbosity::Normal {
+                            self.log.print(&format!(
+                                "The board differs from the server ({} local, {} server moves), reloaded",
+                                local, server
+                            ));
+                        }
+                        if let Some(record) = self.record.as_mut() {
+                            record.set_moves(&game.get_moves());
+                        }
+                        self.game = game;
+                        continue;
+                    }
+                    result => result?,
+                }
             };
+            if let Some(rivals_move) = maybe_rivals_move {
+                self.add_move(rivals_move)?;
+                self.record_move(rivals_move, None, None);
+                if self.verbosity >= Verbosity::Normal {
+                    self.log.print(&format!("Rival's move: {:?}", rivals_move));
+                }
+            }
             if let Some(winner) = maybe_winner {
                 break Ok(winner);
             }
-            if let Some(rivals_move) = maybe_rivals_move {
-                self.add_move(rivals_move)?;
-                println!("Rival's move: {:?}", rivals_move,);
+            if let Some(mv) = self.book.as_ref().and_then(|b| b.choose_move(&self.game)) {
+                if self.verbosity >= Verbosity::Normal {
+                    self.log.print(&format!("My move (book): {:?}", mv));
+                }
+                {
+                    let connection = self.connection.as_mut().ok_or(Error::Invalid)?;
+                    connection.put_move(&mv).await?;
+                }
+                self.add_move(mv)?;
+                self.record_move(mv, None, Some(0));
+                continue;
             }
-            self.compute_suggestions(true, VecDeque::new(), 6)?;
+            let nodes = self.nodes;
+            let started = Instant::now();
+            let depth = self.search_with_permit().await?;
             let maybe_suggestion = self.suggest_move(true);
             if let Ok(suggestion) = maybe_suggestion {
-                println!("My move: {:?}", suggestion.get_move(),);
+                if self.verbosity >= Verbosity::Normal {
+                    self.log
+                        .print(&format!("My move: {:?}", suggestion.get_move()));
+                }
+                if self.verbosity >= Verbosity::Verbose {
+                    self.log.print(&format!(
+                        "\tdepth {}, score {:?}, {} positions in {:.2}s",
+                        depth,
+                        suggestion.get_deep_score(),
+                        self.nodes - nodes,
+                        started.elapsed().as_secs_f64()
+                    ));
+                }
                 let mv = suggestion.get_move();
                 {
                     let connection = self.connection.as_mut().ok_or(Error::Invalid)?;
                     connection.put_move(mv).await?;
                 }
                 self.add_move(*mv)?;
+                self.record_move(*mv, Some(suggestion.get_deep_score()), Some(depth));
             }
         };
         result
@@ -0,0 +1,77 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Output of one game, printed with an optional prefix and appended to an
+/// optional file, the clones share the file
+#[derive(Debug, Clone, Default)]
+pub struct GameLog {
+    prefix: Option<String>,
+    file: Option<Arc<Mutex<File>>>,
+}
+
+impl GameLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Distinguishes the games printed concurrently
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = Some(String::from(prefix));
+        self
+    }
+
+    pub fn with_file(mut self, path: &Path) -> std::io::Result<Self> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        self.file = Some(Arc::new(Mutex::new(file)));
+        Ok(self)
+    }
+
+    /// Prints the message, each line of it is prefixed
+    pub fn print(&self, message: &str) {
+        match self.prefix.as_ref() {
+            Some(prefix) => {
+                let lines: Vec<String> = message
+                    .lines()
+                    .map(|line| format!("[{}] {}", prefix, line))
+                    .collect();
+                println!("{}", lines.join("\n"));
+            }
+            None => println!("{}", message),
+        }
+        if let Some(file) = self.file.as_ref() {
+            let mut file = file.lock().unwrap();
+            if let Err(e) = writeln!(file, "{}", message) {
+                println!("Unable to write the game log: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_appends_to_file() {
+        let path = std::env::temp_dir()
+            .join("five_in_a_row_it_appends_to_file")
+            .join("game.log");
+        let _ = fs::remove_file(&path);
+        let log = GameLog::new()
+            .with_prefix("game 1")
+            .with_file(&path)
+            .unwrap();
+        log.print("Connected");
+        log.clone().print("My move:\nMine(0, 0)");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "Connected\nMy move:\nMine(0, 0)\n"
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,239 @@
+use crate::api::game_connection::GameInfo;
+use crate::game::score::Score;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(_) => write!(f, "IO Error"),
+            Self::Json(_) => write!(f, "Json Error"),
+        }
+    }
+}
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Json(err) => Some(err),
+        }
+    }
+}
+
+/// Milliseconds since the unix epoch
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedMove<M> {
+    pub mv: M,
+    pub timestamp: u64,
+    /// Deep score of the move as evaluated by our engine, `None` for rival's moves
+    pub score: Option<Score>,
+    /// Search depth used to choose the move, `None` for rival's moves and 0
+    /// for the moves taken from the opening book
+    pub depth: Option<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameRecord<M> {
+    pub info: GameInfo,
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+    pub moves: Vec<RecordedMove<M>>,
+    pub winner_id: Option<String>,
+    pub error: Option<String>,
+}
+
+impl<M: Copy> GameRecord<M> {
+    pub fn new(info: GameInfo) -> Self {
+        Self {
+            info,
+            started_at: now(),
+            finished_at: None,
+            moves: Vec::new(),
+            winner_id: None,
+            error: None,
+        }
+    }
+
+    pub fn add_move(&mut self, mv: M, score: Option<Score>, depth: Option<u8>) {
+        self.moves.push(RecordedMove {
+            mv,
+            timestamp: now(),
+            score,
+            depth,
+        });
+    }
+
+    pub fn get_moves(&self) -> Vec<M> {
+        self.moves.iter().map(|m| m.mv).collect()
+    }
+
+    /// Whether we won the game, `None` when there is no winner
+    pub fn is_won(&self) -> Option<bool> {
+        let winner_id = self.winner_id.as_ref()?;
+        Some(self.info.user_id.as_ref() == Some(winner_id))
+    }
+}
+
+impl<M: Copy + PartialEq> GameRecord<M> {
+    /// Replaces the moves by the ones of a reloaded game, the details of the
+    /// moves both lists begin with are kept
+    pub fn set_moves(&mut self, moves: &[M]) {
+        let common = self
+            .moves
+            .iter()
+            .zip(moves.iter())
+            .take_while(|(recorded, mv)| recorded.mv == **mv)
+            .count();
+        self.moves.truncate(common);
+        for mv in moves[common..].iter() {
+            self.add_move(*mv, None, None);
+        }
+    }
+}
+
+impl<M: DeserializeOwned> GameRecord<M> {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+}
+
+/// Stores game records as json files, one file per game
+#[derive(Debug, Clone)]
+pub struct GameRecorder {
+    directory: PathBuf,
+}
+
+impl GameRecorder {
+    pub fn new(directory: &Path) -> Self {
+        Self {
+            directory: directory.to_path_buf(),
+        }
+    }
+
+    /// File of the record named by the game token or the start of the game
+    pub fn get_path<M>(&self, record: &GameRecord<M>) -> PathBuf {
+        let name = record
+            .info
+            .game_token
+            .as_ref()
+            .map_or(format!("game-{}", record.started_at), |t| t.clone());
+        self.directory.join(format!("{}.json", name))
+    }
+
+    pub fn save<M: Serialize>(&self, record: &GameRecord<M>) -> Result<PathBuf, Error> {
+        fs::create_dir_all(&self.directory)?;
+        let path = self.get_path(record);
+        fs::write(&path, serde_json::to_string_pretty(record)?)?;
+        Ok(path)
+    }
+
+    /// Lists the paths of all stored records sorted by name
+    pub fn list(&self) -> Result<Vec<PathBuf>, Error> {
+        let mut paths = fs::read_dir(&self.directory)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+            .collect::<Vec<_>>();
+        paths.sort();
+        Ok(paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::five_in_a_row::mv::FiveInRowMove;
+
+    #[test]
+    fn it_saves_and_loads_record() {
+        let directory = std::env::temp_dir().join("five_in_a_row_it_saves_and_loads_record");
+        let recorder = GameRecorder::new(&directory);
+        let mut record = GameRecord::new(GameInfo {
+            game_token: Some(String::from("token")),
+            user_id: Some(String::from("pl1")),
+            player_cross_id: Some(String::from("pl1")),
+            player_circle_id: Some(String::from("pl2")),
+        });
+        record.add_move(
+            FiveInRowMove::Mine(0, 0),
+            Some(Score::Numeric(1.5)),
+            Some(6),
+        );
+        record.add_move(FiveInRowMove::Rivals(0, 1), None, None);
+        record.winner_id = Some(String::from("pl1"));
+
+        let path = recorder.save(&record).unwrap();
+        assert_eq!(path, directory.join("token.json"));
+        assert_eq!(recorder.list().unwrap(), vec![path.clone()]);
+
+        let loaded = GameRecord::<FiveInRowMove>::load(&path).unwrap();
+        assert_eq!(
+            loaded.get_moves(),
+            vec![FiveInRowMove::Mine(0, 0), FiveInRowMove::Rivals(0, 1)]
+        );
+        assert_eq!(loaded.moves[0].score, Some(Score::Numeric(1.5)));
+        assert_eq!(loaded.moves[0].depth, Some(6));
+        assert_eq!(loaded.is_won(), Some(true));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn it_replaces_moves_keeping_common_beginning() {
+        let mut record = GameRecord::new(GameInfo::default());
+        record.add_move(
+            FiveInRowMove::Mine(0, 0),
+            Some(Score::Numeric(1.5)),
+            Some(6),
+        );
+        record.add_move(FiveInRowMove::Rivals(0, 1), None, None);
+        record.add_move(
+            FiveInRowMove::Mine(1, 1),
+            Some(Score::Numeric(2.0)),
+            Some(4),
+        );
+
+        record.set_moves(&[
+            FiveInRowMove::Mine(0, 0),
+            FiveInRowMove::Rivals(0, 1),
+            FiveInRowMove::Mine(5, 5),
+            FiveInRowMove::Rivals(0, 2),
+        ]);
+        assert_eq!(
+            record.get_moves(),
+            vec![
+                FiveInRowMove::Mine(0, 0),
+                FiveInRowMove::Rivals(0, 1),
+                FiveInRowMove::Mine(5, 5),
+                FiveInRowMove::Rivals(0, 2),
+            ]
+        );
+        assert_eq!(record.moves[0].depth, Some(6));
+        assert_eq!(record.moves[2].score, None);
+        assert_eq!(record.moves[2].depth, None);
+    }
+}
@@ -1,7 +1,8 @@
 use core::cmp::Ordering;
+use serde::{Deserialize, Serialize};
 use std::ops::{Add, Mul, Sub};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum Score {
     Numeric(f64),
     Win,
@@ -32,6 +33,14 @@ impl Score {
             *self * -1.0
         }
     }
+    /// Exact total order, unlike `Ord` it doesn't treat close numbers as equal
+    /// so it is safe to be used for sorting
+    pub fn total_cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Numeric(score), Self::Numeric(other_score)) => score.total_cmp(other_score),
+            _ => Ord::cmp(self, other),
+        }
+    }
     pub fn is_finished(&self) -> bool {
         match self {
             Self::Numeric(_) => false,
@@ -187,6 +196,41 @@ mod tests {
         assert!(Score::Loss == Score::Loss);
     }
 
+    #[test]
+    fn it_orders_close_values_transitively() {
+        let (a, b, c) = (
+            Score::Numeric(1.0),
+            Score::Numeric(1.008),
+            Score::Numeric(1.016),
+        );
+        // the tolerant ordering is not transitive
+        assert_eq!(Ord::cmp(&a, &b), Ordering::Equal);
+        assert_eq!(Ord::cmp(&b, &c), Ordering::Equal);
+        assert_eq!(Ord::cmp(&a, &c), Ordering::Less);
+        assert_eq!(a.total_cmp(&b), Ordering::Less);
+        assert_eq!(b.total_cmp(&c), Ordering::Less);
+        assert_eq!(a.total_cmp(&c), Ordering::Less);
+        assert_eq!(Score::Win.total_cmp(&c), Ordering::Greater);
+        assert_eq!(Score::Loss.total_cmp(&a), Ordering::Less);
+    }
+
+    #[test]
+    fn it_sorts_close_values() {
+        let mut scores = [
+            Score::Numeric(1.008),
+            Score::Win,
+            Score::Numeric(1.0),
+            Score::Loss,
+            Score::Numeric(1.016),
+        ];
+        scores.sort_by(|a, b| a.total_cmp(b));
+        assert!(matches!(scores[0], Score::Loss));
+        assert!(matches!(scores[1], Score::Numeric(s) if s == 1.0));
+        assert!(matches!(scores[2], Score::Numeric(s) if s == 1.008));
+        assert!(matches!(scores[3], Score::Numeric(s) if s == 1.016));
+        assert!(matches!(scores[4], Score::Win));
+    }
+
     #[test]
     fn it_returns_greater_value() {
         assert_eq!(Score::max(&Score::Win, &Score::Loss), &Score::Win);
@@ -62,6 +62,17 @@ impl<G: Game> Suggestion<G> {
     pub fn get_suggestions(&self) -> &Vec<Suggestion<G>> {
         &self.suggestions
     }
+
+    /// The move followed by the best replies computed for each subsequent level
+    pub fn get_principal_variation(&self) -> Vec<G::Move> {
+        let mut variation = vec![self.mv];
+        let mut current = self;
+        while let Some(best) = current.suggestions.first() {
+            variation.push(best.mv);
+            current = best;
+        }
+        variation
+    }
     pub fn add_suggestions(
         &mut self,
         parents: &VecDeque<G::Move>,
@@ -151,4 +162,36 @@ mod tests {
         assert_eq!(*suggestion.get_score(), Score::Numeric(1.0));
         assert_eq!(suggestion.get_deep_score(), Score::Numeric(-1.0));
     }
+
+    #[test]
+    fn it_returns_principal_variation() {
+        let mut suggestion =
+            Suggestion::<FiveInRow>::new(FiveInRowMove::Mine(0, 0), Score::Numeric(1.0));
+        suggestion
+            .add_suggestions(
+                &VecDeque::new(),
+                vec![
+                    Suggestion::new(FiveInRowMove::Rivals(1, 1), Score::Numeric(-1.0)),
+                    Suggestion::new(FiveInRowMove::Rivals(1, 0), Score::Numeric(0.0)),
+                ],
+            )
+            .unwrap();
+        suggestion
+            .add_suggestions(
+                &VecDeque::from([FiveInRowMove::Rivals(1, 1)]),
+                vec![Suggestion::new(
+                    FiveInRowMove::Mine(2, 2),
+                    Score::Numeric(1.0),
+                )],
+            )
+            .unwrap();
+        assert_eq!(
+            suggestion.get_principal_variation(),
+            vec![
+                FiveInRowMove::Mine(0, 0),
+                FiveInRowMove::Rivals(1, 1),
+                FiveInRowMove::Mine(2, 2),
+            ]
+        );
+    }
 }
@@ -1,6 +1,12 @@
+#[path = "analysis/analysis.rs"]
+pub mod analysis;
+
 #[path = "api/api.rs"]
 pub mod api;
 
+#[path = "config.rs"]
+pub mod config;
+
 #[path = "five_in_a_row/five_in_a_row.rs"]
 pub mod five_in_a_row;
 
@@ -9,3 +15,9 @@ pub mod game;
 
 #[path = "game/gameplay.rs"]
 pub mod gameplay;
+
+#[path = "scheduler.rs"]
+pub mod scheduler;
+
+#[path = "server/server.rs"]
+pub mod server;
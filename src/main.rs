@@ -1,52 +1,420 @@
-#[path = "lib.rs"]
-mod lib;
-
-use lib::{api, five_in_a_row, game, gameplay};
+use clap::{Args, Parser, Subcommand};
+use game_play::analysis::{blunders, load_game, stats::Stats};
+use game_play::api::jobs_cz::{fetch, register, JobsApi};
+use game_play::config::{
+    Config, CredentialStore, Credentials, Error as ConfigError, ServerConfig, DEFAULT_CONFIG_FILE,
+    DEFAULT_CREDENTIALS_FILE, DEFAULT_GAME_FILE,
+};
+use game_play::five_in_a_row::book::OpeningBook;
+use game_play::five_in_a_row::eval::EvalParams;
+use game_play::five_in_a_row::mv::FiveInRowMove;
+use game_play::game::record::{GameRecord, GameRecorder};
+use game_play::gameplay::{Verbosity, DEFAULT_SEARCH_DEPTH};
+use game_play::scheduler::{GameSettings, Scheduler};
 use std::boxed::Box;
 use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
-    let user_id = String::from("***");
-    let user_token = String::from("***");
-
-    let mut gameplays = 0;
-    let mut wins = 0;
-    let mut losses = 0;
-    let mut errors = 0;
-    let api = api::jobs_cz::JobsApi::new(&user_id, &user_token);
-
-    loop {
-        gameplays += 1;
-
-        // create a new game
-        let mut maybe_game_play = gameplay::GamePlay::from_api(api.clone()).await;
-        if let Ok(game_play) = maybe_game_play.as_mut() {
-            // start to play and wait for the winner id
-            let maybe_winner = game_play.play().await;
-
-            if let Ok(winner) = maybe_winner {
-                if winner.eq(&user_id) {
-                    wins += 1;
-                    println!("I won the game ✌🥇");
-                } else {
-                    losses += 1;
-                    println!("I lost the game 😢");
-                }
-            } else {
-                println!("No winner");
-                errors += 1;
+/// Five in a row bot playing on piskvorky.jobs.cz
+#[derive(Parser)]
+struct Cli {
+    /// Configuration file with the credentials and default paths
+    #[arg(long, env = "PISKVORKY_CONFIG", default_value = DEFAULT_CONFIG_FILE)]
+    config: PathBuf,
+    /// File with the credentials of the registered profiles
+    #[arg(long, env = "PISKVORKY_CREDENTIALS")]
+    credentials: Option<PathBuf>,
+    /// Print the search details of each move
+    #[arg(short, long, global = true)]
+    verbose: bool,
+    /// Print only the results of the games
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Play games against the bots on the server
+    Play(Box<PlayArgs>),
+    /// Register a new user and store its credentials as a profile
+    Register(RegisterArgs),
+    /// List the stored profiles
+    Profiles,
+    /// Report the blunders of a recorded game
+    Analyse {
+        /// Game record or psq file
+        file: PathBuf,
+        /// Search depth used to evaluate the positions
+        #[arg(short, long, default_value_t = 4)]
+        depth: u8,
+    },
+    /// Summarize the results of the recorded games
+    Stats {
+        /// Directory with the game records
+        #[arg(long, env = "GAME_RECORDS_DIR")]
+        records: Option<PathBuf>,
+    },
+}
+
+/// Connection to the game server, overrides the `[server]` table of the
+/// configuration file
+#[derive(Args)]
+struct ServerArgs {
+    /// Address of the game server
+    #[arg(long, env = "PISKVORKY_BASE_URL")]
+    base_url: Option<String>,
+    /// Timeout of a request in seconds
+    #[arg(long)]
+    timeout: Option<f64>,
+    /// Timeout of establishing a connection in seconds
+    #[arg(long)]
+    connect_timeout: Option<f64>,
+    /// Proxy for all the requests
+    #[arg(long)]
+    proxy: Option<String>,
+    #[arg(long)]
+    user_agent: Option<String>,
+}
+
+impl ServerArgs {
+    fn get_client(self, config: &Config) -> Result<fetch::JobsApi, fetch::Error> {
+        let server = config.server.merge(&ServerConfig {
+            base_url: self.base_url,
+            timeout: self.timeout,
+            connect_timeout: self.connect_timeout,
+            proxy: self.proxy,
+            user_agent: self.user_agent,
+        });
+        fetch::JobsApi::from_settings(&server.get_settings())
+    }
+}
+
+#[derive(Args)]
+struct PlayArgs {
+    #[arg(long, env = "PISKVORKY_USER_ID", hide_env_values = true)]
+    user_id: Option<String>,
+    #[arg(long, env = "PISKVORKY_USER_TOKEN", hide_env_values = true)]
+    user_token: Option<String>,
+    /// Stored profile to play with, may be repeated; the credentials given
+    /// explicitly, the configured ones or the default profile otherwise
+    #[arg(short, long = "profile")]
+    profiles: Vec<String>,
+    /// Number of games to play, unlimited by default
+    #[arg(short = 'n', long)]
+    games: Option<usize>,
+    /// Maximal search depth
+    #[arg(short, long, default_value_t = DEFAULT_SEARCH_DEPTH)]
+    depth: u8,
+    /// Time limit of the search for one move in seconds
+    #[arg(short = 't', long)]
+    move_time: Option<f64>,
+    /// Evaluation weights, a .toml or json file
+    #[arg(long, env = "EVAL_PARAMS")]
+    weights: Option<PathBuf>,
+    /// Opening book file
+    #[arg(long, env = "OPENING_BOOK")]
+    book: Option<PathBuf>,
+    /// Directory for the game records
+    #[arg(long, env = "GAME_RECORDS_DIR")]
+    records: Option<PathBuf>,
+    /// Save an SVG diagram of each game next to its record
+    #[arg(long)]
+    diagrams: bool,
+    /// File keeping the token of the game in progress, the game is resumed
+    /// after a restart
+    #[arg(long, env = "PISKVORKY_GAME_FILE")]
+    game_file: Option<PathBuf>,
+    /// Resume the game instead of connecting to a new one
+    #[arg(long)]
+    game_token: Option<String>,
+    /// Append all the requests and responses to the file
+    #[arg(long, conflicts_with = "replay")]
+    record: Option<PathBuf>,
+    /// Play the recorded requests and responses instead of the server,
+    /// one game by default and without saving the game records; the moves
+    /// have to be the recorded ones so the search can't be limited by time
+    #[arg(long, conflicts_with_all = ["concurrency", "all_accounts", "move_time"])]
+    replay: Option<PathBuf>,
+    /// Number of games played at once by each account
+    #[arg(short = 'c', long, default_value_t = 1)]
+    concurrency: usize,
+    /// Play also with the accounts of the configuration file and with all
+    /// the stored profiles
+    #[arg(long)]
+    all_accounts: bool,
+    /// Number of searches running at once, all the cores by default
+    #[arg(long)]
+    threads: Option<usize>,
+    /// Directory for a log file of each game
+    #[arg(long, env = "GAME_LOGS_DIR")]
+    logs: Option<PathBuf>,
+    #[command(flatten)]
+    server: ServerArgs,
+}
+
+#[derive(Args)]
+struct RegisterArgs {
+    #[arg(long)]
+    nickname: String,
+    #[arg(long)]
+    email: String,
+    /// Name of the profile, the nickname by default
+    #[arg(long)]
+    profile: Option<String>,
+    /// Use the profile when none is selected
+    #[arg(long)]
+    default: bool,
+    #[command(flatten)]
+    server: ServerArgs,
+}
+
+fn get_records_dir(records: Option<PathBuf>, config: &Config) -> PathBuf {
+    records
+        .or_else(|| config.records_dir.clone())
+        .unwrap_or_else(|| PathBuf::from("games"))
+}
+
+/// Each concurrently played game keeps its token in its own file
+fn get_game_file(path: &Path, slot: usize) -> PathBuf {
+    if slot == 0 {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, slot, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, slot),
+    };
+    path.with_file_name(name)
+}
+
+fn get_credentials_file(credentials: Option<PathBuf>, config: &Config) -> PathBuf {
+    credentials
+        .or_else(|| config.credentials_file.clone())
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CREDENTIALS_FILE))
+}
+
+/// Accounts of the selected profiles, or the ones given explicitly or
+/// configured, or the default profile
+fn get_accounts(
+    args: &PlayArgs,
+    config: &Config,
+    store: &CredentialStore,
+) -> Result<Vec<Credentials>, Box<dyn Error>> {
+    let mut accounts = Vec::new();
+    for profile in args.profiles.iter() {
+        accounts.push(store.get(profile)?);
+    }
+    if accounts.is_empty() {
+        match config.get_credentials(args.user_id.clone(), args.user_token.clone()) {
+            Ok(credentials) => accounts.push(credentials),
+            Err(e) => match store.get_default() {
+                Some(credentials) => accounts.push(credentials),
+                None if !args.all_accounts => return Err(e.into()),
+                None => {}
+            },
+        }
+    }
+    if args.all_accounts {
+        accounts.extend(config.accounts.iter().cloned());
+        accounts.extend(store.profiles.values().cloned());
+    }
+    let mut unique: Vec<Credentials> = Vec::new();
+    for credentials in accounts {
+        if !unique.contains(&credentials) {
+            unique.push(credentials);
+        }
+    }
+    if unique.is_empty() {
+        return Err(Box::new(ConfigError::MissingCredentials));
+    }
+    Ok(unique)
+}
+
+async fn play(
+    args: PlayArgs,
+    config: &Config,
+    store: &CredentialStore,
+    verbosity: Verbosity,
+) -> Result<(), Box<dyn Error>> {
+    let accounts = get_accounts(&args, config, store)?;
+    let params = match args.weights.as_ref().or(config.weights.as_ref()) {
+        Some(path) => Arc::new(EvalParams::load(path)?),
+        None => Arc::default(),
+    };
+    let book = match args.book.as_ref().or(config.book.as_ref()) {
+        Some(path) => Some(OpeningBook::load(path)?),
+        None => None,
+    };
+    // the clones of the client share its rate limit
+    let client = match (args.record, args.replay.as_ref()) {
+        (_, Some(replay)) => fetch::JobsApi::from_recording(replay)?,
+        (Some(record), None) => args.server.get_client(config)?.record_to(&record)?,
+        (None, None) => args.server.get_client(config)?,
+    };
+    let game_file = args
+        .game_file
+        .or_else(|| config.game_file.clone())
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_GAME_FILE));
+
+    let mut slots = Vec::new();
+    for credentials in accounts.iter() {
+        for _ in 0..args.concurrency.max(1) {
+            let mut api = JobsApi::new(&credentials.user_id, &credentials.user_token)
+                .with_client(client.clone());
+            if args.replay.is_none() {
+                api = api.with_game_file(&get_game_file(&game_file, slots.len()));
             }
+            slots.push(api);
+        }
+    }
+    if let Some(first) = slots.first_mut() {
+        *first = first.clone().with_game_token(args.game_token);
+    }
+
+    let settings = GameSettings {
+        search_depth: args.depth,
+        move_time: args.move_time.map(Duration::from_secs_f64),
+        verbosity,
+        params,
+        book,
+        records_dir: match args.replay {
+            Some(_) => None,
+            None => Some(get_records_dir(args.records, config)),
+        },
+        logs_dir: args.logs.or_else(|| config.logs_dir.clone()),
+        diagrams: args.diagrams,
+    };
+    let games = match args.replay {
+        Some(_) => args.games.or(Some(1)),
+        None => args.games,
+    };
+    let mut scheduler = Scheduler::new(slots, settings).with_games(games);
+    if let Some(threads) = args.threads {
+        scheduler = scheduler.with_cpu_budget(threads);
+    }
+    let summary = scheduler.run().await;
+    println!("Summary of all the games:\n{}", summary.format());
+    Ok(())
+}
+
+async fn register(
+    args: RegisterArgs,
+    config: &Config,
+    credentials_file: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let mut store = CredentialStore::load_or_default(credentials_file)?;
+    let profile = args
+        .profile
+        .clone()
+        .unwrap_or_else(|| args.nickname.clone());
+    if store.profiles.contains_key(&profile) {
+        return Err(Box::new(ConfigError::ProfileExists(profile)));
+    }
+    let mut client = args.server.get_client(config)?;
+    let response = register::invoke_registration(
+        &mut client,
+        &register::RegisterPayload {
+            nickname: args.nickname.clone(),
+            email: args.email,
+        },
+    )
+    .await?;
+    let credentials = Credentials {
+        user_id: response.userId.clone(),
+        user_token: response.userToken,
+    };
+    let mut stored = store.add(&profile, credentials.clone());
+    if stored.is_ok() {
+        if args.default {
+            store.default = Some(profile.clone());
+        }
+        stored = store.save(credentials_file);
+    }
+    if let Err(e) = stored {
+        // the account exists already, its token can't be obtained again
+        eprintln!(
+            "Registered {} but the credentials couldn't be stored, keep them: user id {}, user token {}",
+            args.nickname, credentials.user_id, credentials.user_token
+        );
+        return Err(Box::new(e));
+    }
+    println!(
+        "Registered {} with user id {}, stored as profile {} in {}",
+        args.nickname,
+        response.userId,
+        profile,
+        credentials_file.display()
+    );
+    Ok(())
+}
+
+fn profiles(credentials_file: &Path) -> Result<(), Box<dyn Error>> {
+    let store = CredentialStore::load_or_default(credentials_file)?;
+    if store.profiles.is_empty() {
+        println!("No profiles stored in {}", credentials_file.display());
+    }
+    for (name, credentials) in store.profiles.iter() {
+        let default = if store.default.as_ref() == Some(name) {
+            " (default)"
         } else {
-            println!("Error in the game 😢");
-            errors += 1;
+            ""
+        };
+        println!("{}: {}{}", name, credentials.user_id, default);
+    }
+    Ok(())
+}
+
+fn analyse(file: &Path, depth: u8) -> Result<(), Box<dyn Error>> {
+    let game = load_game(file)?;
+    let analyses = blunders::analyse_game(&game, depth)?;
+    print!("{}", blunders::format_report(&analyses));
+    Ok(())
+}
+
+fn stats(records_dir: &Path) -> Result<(), Box<dyn Error>> {
+    let mut stats = Stats::default();
+    let paths = if records_dir.is_dir() {
+        GameRecorder::new(records_dir).list()?
+    } else {
+        Vec::new()
+    };
+    for path in paths.iter() {
+        match GameRecord::<FiveInRowMove>::load(path) {
+            Ok(record) => stats.add(&record),
+            Err(e) => println!("Skipping {} ({})", path.display(), e),
         }
+    }
+    println!("Games recorded in {}:", records_dir.display());
+    print!("{}", stats.format());
+    Ok(())
+}
 
-        println!("==========================\n\n");
-        println!("\t- wins: {}", wins);
-        println!("\t- losses: {}", losses);
-        println!("\t- errors: {}", errors);
-        println!("\t- total games: {}", gameplays);
-        println!("==========================\n\n");
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+    let config = Config::load_or_default(&cli.config)?;
+    let verbosity = if cli.quiet {
+        Verbosity::Quiet
+    } else if cli.verbose {
+        Verbosity::Verbose
+    } else {
+        Verbosity::Normal
+    };
+
+    let credentials_file = get_credentials_file(cli.credentials, &config);
+
+    match cli.command {
+        Command::Play(args) => {
+            let store = CredentialStore::load_or_default(&credentials_file)?;
+            play(*args, &config, &store, verbosity).await
+        }
+        Command::Register(args) => register(args, &config, &credentials_file).await,
+        Command::Profiles => profiles(&credentials_file),
+        Command::Analyse { file, depth } => analyse(&file, depth),
+        Command::Stats { records } => stats(&get_records_dir(records, &config)),
     }
 }
@@ -0,0 +1,280 @@
+use crate::analysis::stats::Stats;
+use crate::api::game_connection::GameConnection;
+use crate::api::jobs_cz::JobsApi;
+use crate::five_in_a_row::svg::{self, SvgOptions};
+use crate::five_in_a_row::{book::OpeningBook, eval::EvalParams, FiveInRow};
+use crate::game::log::GameLog;
+use crate::game::record::{self, GameRecorder};
+use crate::gameplay::{GamePlay, Verbosity, DEFAULT_SEARCH_DEPTH};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+/// Settings of each played game
+#[derive(Debug, Clone)]
+pub struct GameSettings {
+    pub search_depth: u8,
+    pub move_time: Option<Duration>,
+    pub verbosity: Verbosity,
+    pub params: Arc<EvalParams>,
+    pub book: Option<OpeningBook>,
+    /// Directory for the game records, no records are saved without it
+    pub records_dir: Option<PathBuf>,
+    /// Directory for a log file of each game
+    pub logs_dir: Option<PathBuf>,
+    /// Save an SVG diagram of each game next to its record
+    pub diagrams: bool,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            search_depth: DEFAULT_SEARCH_DEPTH,
+            move_time: None,
+            verbosity: Verbosity::Normal,
+            params: Arc::default(),
+            book: None,
+            records_dir: None,
+            logs_dir: None,
+            diagrams: false,
+        }
+    }
+}
+
+/// Results of the games of each account
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Summary {
+    pub accounts: BTreeMap<String, Stats>,
+}
+
+impl Summary {
+    pub fn get_total(&self) -> Stats {
+        let mut total = Stats::default();
+        for stats in self.accounts.values() {
+            total.merge(stats);
+        }
+        total
+    }
+
+    /// Combined statistics, followed by each account's ones when there are
+    /// more of them
+    pub fn format(&self) -> String {
+        let mut report = self.get_total().format();
+        if self.accounts.len() > 1 {
+            for (user_id, stats) in self.accounts.iter() {
+                report.push_str(&format!("{}:\n{}", user_id, stats.format()));
+            }
+        }
+        report
+    }
+}
+
+/// Plays games on several connections concurrently, the connections share
+/// the rate limit of their client and the searches share the cpu budget
+pub struct Scheduler {
+    /// Connection of each concurrently played game, several of them may
+    /// belong to one account
+    pub slots: Vec<JobsApi>,
+    pub settings: GameSettings,
+    /// Total number of games, unlimited by default
+    pub games: Option<usize>,
+    /// Permits of the searches running at once
+    pub cpu: Arc<Semaphore>,
+}
+
+impl Scheduler {
+    /// The searches may use all the available cores
+    pub fn new(slots: Vec<JobsApi>, settings: GameSettings) -> Self {
+        let cores = thread::available_parallelism().map_or(1, |n| n.get());
+        Self {
+            slots,
+            settings,
+            games: None,
+            cpu: Arc::new(Semaphore::new(cores)),
+        }
+    }
+
+    pub fn with_games(mut self, games: Option<usize>) -> Self {
+        self.games = games;
+        self
+    }
+
+    /// Limits the number of the searches running at once
+    pub fn with_cpu_budget(mut self, searches: usize) -> Self {
+        self.cpu = Arc::new(Semaphore::new(searches.max(1)));
+        self
+    }
+
+    /// Plays until the number of games is reached, the slots start a new
+    /// game as soon as their previous one finishes
+    pub async fn run(self) -> Summary {
+        let shared = Arc::new(Shared {
+            settings: self.settings,
+            games: self.games,
+            cpu: self.cpu,
+            prefixed: self.slots.len() > 1,
+            started: AtomicUsize::new(0),
+            summary: Mutex::new(Summary::default()),
+        });
+        let handles: Vec<_> = self
+            .slots
+            .into_iter()
+            .map(|api| tokio::spawn(play_slot(api, shared.clone())))
+            .collect();
+        for handle in handles {
+            if let Err(e) = handle.await {
+                println!("A game slot failed: {}", e);
+            }
+        }
+        let summary = shared.summary.lock().unwrap().clone();
+        summary
+    }
+}
+
+struct Shared {
+    settings: GameSettings,
+    games: Option<usize>,
+    cpu: Arc<Semaphore>,
+    /// The output of the games is distinguished by their number
+    prefixed: bool,
+    started: AtomicUsize,
+    summary: Mutex<Summary>,
+}
+
+impl Shared {
+    fn get_log(&self, number: usize) -> GameLog {
+        let mut log = GameLog::new();
+        if self.prefixed {
+            log = log.with_prefix(&format!("game {}", number));
+        }
+        if let Some(logs_dir) = self.settings.logs_dir.as_ref() {
+            let path = logs_dir.join(format!("{}-{}.log", record::now(), number));
+            match log.clone().with_file(&path) {
+                Ok(with_file) => log = with_file,
+                Err(e) => log.print(&format!("Unable to create the game log: {}", e)),
+            }
+        }
+        log
+    }
+
+    fn configure(&self, game_play: &mut GamePlay<FiveInRow, JobsApi>, log: GameLog) {
+        let settings = &self.settings;
+        if let Some(records_dir) = settings.records_dir.as_ref() {
+            game_play.recorder = Some(GameRecorder::new(records_dir));
+        }
+        game_play.game.params = settings.params.clone();
+        game_play.search_depth = settings.search_depth;
+        game_play.move_time = settings.move_time;
+        game_play.verbosity = settings.verbosity;
+        if let Some(book) = settings.book.as_ref() {
+            game_play.book = Some(Box::new(book.clone()));
+        }
+        game_play.log = log;
+        game_play.cpu = Some(self.cpu.clone());
+    }
+
+    /// Saves the diagram of the game with numbered moves next to its record
+    fn save_diagram(&self, game_play: &GamePlay<FiveInRow, JobsApi>, log: &GameLog) {
+        let (records_dir, record) = match (
+            self.settings.records_dir.as_ref(),
+            game_play.record.as_ref(),
+        ) {
+            (Some(records_dir), Some(record)) if self.settings.diagrams => (records_dir, record),
+            _ => return,
+        };
+        let path = GameRecorder::new(records_dir)
+            .get_path(record)
+            .with_extension("svg");
+        let options = SvgOptions {
+            numbering: true,
+            ..SvgOptions::default()
+        };
+        if let Err(e) = svg::save_svg(&game_play.game, &options, &path) {
+            log.print(&format!("Unable to save the game diagram: {}", e));
+        }
+    }
+}
+
+async fn play_slot(mut api: JobsApi, shared: Arc<Shared>) {
+    let user_id = api.get_game_info().user_id.unwrap_or_default();
+    loop {
+        let number = shared.started.fetch_add(1, Ordering::SeqCst) + 1;
+        if shared.games.is_some_and(|games| number > games) {
+            break;
+        }
+        let log = shared.get_log(number);
+        let game_api = api.clone().with_log(log.clone());
+        // only the first game of the slot may be resumed explicitly
+        api = api.with_game_token(None);
+
+        let record = match GamePlay::from_api(game_api).await {
+            Ok(mut game_play) => {
+                shared.configure(&mut game_play, log.clone());
+                match game_play.play().await {
+                    Ok(winner) if winner == user_id => log.print("I won the game ✌🥇"),
+                    Ok(_) => log.print("I lost the game 😢"),
+                    Err(e) => log.print(&format!("No winner ({})", e)),
+                }
+                shared.save_diagram(&game_play, &log);
+                game_play.record
+            }
+            Err(e) => {
+                log.print(&format!("Error in the game 😢 ({})", e));
+                None
+            }
+        };
+
+        let report = {
+            let mut summary = shared.summary.lock().unwrap();
+            let stats = summary.accounts.entry(user_id.clone()).or_default();
+            match record.as_ref() {
+                Some(record) => stats.add(record),
+                None => {
+                    stats.games += 1;
+                    stats.errors += 1;
+                }
+            }
+            summary.format()
+        };
+        // one print keeps the report together when the games run concurrently
+        log.print(&format!(
+            "==========================\n{}==========================",
+            report
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_summarizes_accounts() {
+        let mut summary = Summary::default();
+        summary.accounts.insert(
+            String::from("first"),
+            Stats {
+                games: 2,
+                wins: 2,
+                ..Stats::default()
+            },
+        );
+        assert!(!summary.format().contains("first:"));
+        summary.accounts.insert(
+            String::from("second"),
+            Stats {
+                games: 1,
+                errors: 1,
+                ..Stats::default()
+            },
+        );
+        assert_eq!(summary.get_total().games, 3);
+        let report = summary.format();
+        assert!(report.contains("total games: 3"));
+        assert!(report.contains("first:") && report.contains("second:"));
+    }
+}
@@ -0,0 +1,140 @@
+#[path = "state.rs"]
+pub mod state;
+
+use crate::game::record::now;
+use crate::server::state::{Options, Response, ServerState};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::FutureExt;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+pub type SharedState = Arc<Mutex<ServerState>>;
+
+/// Parses the raw body, the clients don't always send the json content type
+fn handle<P: DeserializeOwned>(
+    state: &SharedState,
+    body: &str,
+    action: impl FnOnce(&mut ServerState, P) -> Response,
+) -> (StatusCode, Json<Value>) {
+    let value: Value = match serde_json::from_str(body) {
+        Ok(value) => value,
+        Err(e) => return to_response((400, json!({ "statusCode": 400, "error": e.to_string() }))),
+    };
+    let mut state = state.lock().unwrap();
+    let token = value["userToken"].as_str().unwrap_or_default();
+    if state.is_rate_limited(token) {
+        return to_response((
+            429,
+            json!({ "statusCode": 429, "error": "Too many requests" }),
+        ));
+    }
+    state.expire_games(now());
+    let response = match serde_json::from_value(value) {
+        Ok(payload) => action(&mut state, payload),
+        Err(e) => (400, json!({ "statusCode": 400, "error": e.to_string() })),
+    };
+    if let Err(e) = state.save() {
+        println!("Unable to save the server data: {}", e);
+    }
+    to_response(response)
+}
+
+fn to_response((status, body): Response) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+        Json(body),
+    )
+}
+
+/// Routes of the piskvorky.jobs.cz api
+pub fn router(state: SharedState) -> Router {
+    Router::new()
+        .route(
+            "/api/v1/user",
+            post(|State(s): State<SharedState>, body: String| async move {
+                handle(&s, &body, ServerState::register)
+            }),
+        )
+        .route(
+            "/api/v1/connect",
+            post(|State(s): State<SharedState>, body: String| async move {
+                handle(&s, &body, ServerState::connect)
+            }),
+        )
+        .route(
+            "/api/v1/play",
+            post(|State(s): State<SharedState>, body: String| async move {
+                handle(&s, &body, ServerState::play)
+            }),
+        )
+        .route(
+            "/api/v1/checkStatus",
+            post(|State(s): State<SharedState>, body: String| async move {
+                handle(&s, &body, |state, payload| {
+                    state.check_status(payload, false)
+                })
+            }),
+        )
+        .route(
+            "/api/v1/checkLastStatus",
+            post(|State(s): State<SharedState>, body: String| async move {
+                handle(&s, &body, |state, payload| {
+                    state.check_status(payload, true)
+                })
+            }),
+        )
+        .route(
+            "/api/v1/leaderboard",
+            get(|State(s): State<SharedState>| async move {
+                let mut state = s.lock().unwrap();
+                state.expire_games(now());
+                Json(json!({ "statusCode": 200, "players": state.get_leaderboard() }))
+            }),
+        )
+        .with_state(state)
+}
+
+/// Serves the api until the server fails
+pub async fn serve(listener: TcpListener, state: SharedState) -> std::io::Result<()> {
+    axum::serve(listener, router(state)).await
+}
+
+/// Server implementing the piskvorky.jobs.cz api on a local port, used to
+/// test the client without the real server
+pub struct MockServer {
+    pub address: SocketAddr,
+    pub state: SharedState,
+    handle: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Starts the server on a free port of the loopback interface
+    pub async fn start(options: Options) -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(ServerState::new(options)));
+        let handle = tokio::spawn(serve(listener, state.clone()).map(|r| r.unwrap()));
+        Ok(Self {
+            address,
+            state,
+            handle,
+        })
+    }
+
+    pub fn get_base_url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
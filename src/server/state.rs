@@ -0,0 +1,701 @@
+use crate::api::game_connection::NoConnection;
+use crate::api::jobs_cz::{
+    connect::{ConnectPayload, ConnectResponse},
+    play::PlayPayload,
+    register::{RegisterPayload, RegisterResponse},
+    status::{Coordinate, StatusPayload, StatusResponse},
+};
+use crate::five_in_a_row::FiveInRow;
+use crate::game::record::now;
+use crate::gameplay::{GamePlay, Verbosity};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(_) => write!(f, "IO Error"),
+            Self::Json(e) => write!(f, "Invalid server data ({})", e),
+        }
+    }
+}
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Json(err) => Some(err),
+        }
+    }
+}
+
+/// Status code of the responses about a finished game
+pub const FINISHED: u16 = 226;
+
+/// Opponent of the connecting players
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rival {
+    /// Plays the moves in turn, the occupied ones are skipped and the
+    /// engine takes over when they run out
+    Scripted(Vec<(i32, i32)>),
+    /// Plays the moves suggested by the engine searching to the depth
+    Engine(u8),
+    /// Another connecting player
+    Player,
+}
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub rival: Rival,
+    /// The built-in rival plays crosses and moves first
+    pub rival_starts: bool,
+    /// Requests of a user coming more often get 429 Too Many Requests
+    pub min_interval: Option<Duration>,
+    /// The player on the move loses when they don't play in time
+    pub turn_timeout: Option<Duration>,
+    /// Number of the first accepted moves answered by 500 Internal Server
+    /// Error as if the response got lost
+    pub lost_responses: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            rival: Rival::Engine(1),
+            rival_starts: false,
+            min_interval: None,
+            turn_timeout: None,
+            lost_responses: 0,
+        }
+    }
+}
+
+/// Id of the built-in rival
+pub const RIVAL_ID: &str = "rival";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct User {
+    pub id: String,
+    pub nickname: String,
+    pub email: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Game {
+    pub id: String,
+    pub cross_id: Option<String>,
+    pub circle_id: Option<String>,
+    pub moves: Vec<(String, i32, i32)>,
+    pub winner_id: Option<String>,
+    /// Milliseconds since the unix epoch of the last move or of the start
+    pub updated_at: u64,
+    /// The loser ran out of time
+    #[serde(default)]
+    pub timed_out: bool,
+    /// Position in the script of a scripted rival
+    #[serde(default)]
+    script_index: usize,
+}
+
+impl Game {
+    pub fn get_actual_player_id(&self) -> Option<&String> {
+        if self.winner_id.is_some() {
+            return None;
+        }
+        let (cross_id, circle_id) = (self.cross_id.as_ref()?, self.circle_id.as_ref()?);
+        Some(if self.moves.len().is_multiple_of(2) {
+            cross_id
+        } else {
+            circle_id
+        })
+    }
+
+    pub fn has_player(&self, user_id: &str) -> bool {
+        self.cross_id.as_deref() == Some(user_id) || self.circle_id.as_deref() == Some(user_id)
+    }
+
+    /// Position from the player's point of view
+    pub fn get_position(&self, player_id: &str) -> FiveInRow {
+        FiveInRow::from_api_coordinates(self.get_coordinates(), player_id)
+    }
+
+    fn get_coordinates(&self) -> Vec<Coordinate> {
+        self.moves
+            .iter()
+            .map(|(player_id, x, y)| Coordinate {
+                playerId: player_id.clone(),
+                x: *x,
+                y: *y,
+            })
+            .collect()
+    }
+
+    fn get_status(&self, last_only: bool) -> (u16, StatusResponse) {
+        let mut coordinates = self.get_coordinates();
+        if last_only {
+            coordinates = coordinates.pop().into_iter().collect();
+        }
+        let status_code = if self.winner_id.is_some() {
+            FINISHED
+        } else {
+            200
+        };
+        (
+            status_code,
+            StatusResponse {
+                statusCode: status_code,
+                playerCrossId: self.cross_id.clone(),
+                playerCircleId: self.circle_id.clone(),
+                actualPlayerId: self.get_actual_player_id().cloned(),
+                winnerId: self.winner_id.clone(),
+                coordinates,
+            },
+        )
+    }
+
+    fn put_move(&mut self, player_id: &str, x: i32, y: i32) {
+        self.moves.push((String::from(player_id), x, y));
+        self.updated_at = now();
+        if self.get_position(player_id).get_winning_line().is_some() {
+            self.winner_id = Some(String::from(player_id));
+        }
+    }
+}
+
+/// Status code and json body of a response
+pub type Response = (u16, Value);
+
+fn respond<T: Serialize>(status: u16, body: &T) -> Response {
+    (status, json!(body))
+}
+
+fn error(status: u16, message: &str) -> Response {
+    (status, json!({ "statusCode": status, "error": message }))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[allow(non_snake_case)]
+pub struct LeaderboardEntry {
+    pub userId: String,
+    pub nickname: String,
+    pub games: usize,
+    pub wins: usize,
+    pub losses: usize,
+}
+
+/// Users and games of the server implementing the piskvorky.jobs.cz api
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ServerState {
+    #[serde(skip)]
+    pub options: Options,
+    /// Users by their tokens
+    pub users: HashMap<String, User>,
+    /// Games by their tokens
+    pub games: HashMap<String, Game>,
+    #[serde(skip)]
+    last_requests: HashMap<String, Instant>,
+    next_id: u64,
+    /// File where the users and games are persisted
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    /// The data changed since they were saved
+    #[serde(skip)]
+    changed: bool,
+}
+
+impl ServerState {
+    pub fn new(options: Options) -> Self {
+        Self {
+            options,
+            next_id: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Loads the users and games from the file when it exists, the changes
+    /// are saved into it
+    pub fn load(path: &Path, options: Options) -> Result<Self, Error> {
+        let mut state = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(path)?)?
+        } else {
+            Self::new(options.clone())
+        };
+        state.options = options;
+        state.path = Some(path.to_path_buf());
+        Ok(state)
+    }
+
+    /// Saves the data when they changed, the file is replaced atomically
+    pub fn save(&mut self) -> Result<(), Error> {
+        let path = match self.path.as_ref() {
+            Some(path) if self.changed => path,
+            _ => return Ok(()),
+        };
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, serde_json::to_string(self)?)?;
+        fs::rename(&temp, path)?;
+        self.changed = false;
+        Ok(())
+    }
+
+    /// Ends the games whose player on the move ran out of time, the rival
+    /// wins them
+    pub fn expire_games(&mut self, now: u64) {
+        let timeout = match self.options.turn_timeout {
+            Some(timeout) => timeout.as_millis() as u64,
+            None => return,
+        };
+        for game in self.games.values_mut() {
+            let actual = match game.get_actual_player_id() {
+                Some(actual) => actual.clone(),
+                None => continue,
+            };
+            if now.saturating_sub(game.updated_at) > timeout {
+                game.winner_id = if game.cross_id.as_ref() == Some(&actual) {
+                    game.circle_id.clone()
+                } else {
+                    game.cross_id.clone()
+                };
+                game.timed_out = true;
+                self.changed = true;
+            }
+        }
+    }
+
+    /// Players ordered by the number of wins and then by fewer losses
+    pub fn get_leaderboard(&self) -> Vec<LeaderboardEntry> {
+        let mut entries = self
+            .users
+            .values()
+            .map(|user| {
+                let finished = self
+                    .games
+                    .values()
+                    .filter(|g| g.winner_id.is_some() && g.has_player(&user.id));
+                let (mut games, mut wins) = (0, 0);
+                for game in finished {
+                    games += 1;
+                    if game.winner_id.as_ref() == Some(&user.id) {
+                        wins += 1;
+                    }
+                }
+                LeaderboardEntry {
+                    userId: user.id.clone(),
+                    nickname: user.nickname.clone(),
+                    games,
+                    wins,
+                    losses: games - wins,
+                }
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| {
+            b.wins
+                .cmp(&a.wins)
+                .then(a.losses.cmp(&b.losses))
+                .then(a.nickname.cmp(&b.nickname))
+        });
+        entries
+    }
+
+    fn generate_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{}-{:08x}", prefix, self.next_id * 2654435761 % (1 << 32))
+    }
+
+    /// Whether the request of the user (identified by the token) comes too
+    /// early after the previous one
+    pub fn is_rate_limited(&mut self, token: &str) -> bool {
+        let min_interval = match self.options.min_interval {
+            Some(min_interval) => min_interval,
+            None => return false,
+        };
+        let now = Instant::now();
+        match self.last_requests.get(token) {
+            Some(last) if now.duration_since(*last) < min_interval => true,
+            _ => {
+                self.last_requests.insert(String::from(token), now);
+                false
+            }
+        }
+    }
+
+    pub fn register(&mut self, payload: RegisterPayload) -> Response {
+        if payload.nickname.is_empty() || !payload.email.contains('@') {
+            return error(400, "Invalid nickname or email");
+        }
+        if self.users.values().any(|u| u.nickname == payload.nickname) {
+            return error(409, "The nickname is taken");
+        }
+        self.changed = true;
+        let user_id = self.generate_id("user");
+        let user_token = self.generate_id("token");
+        self.users.insert(
+            user_token.clone(),
+            User {
+                id: user_id.clone(),
+                nickname: payload.nickname,
+                email: payload.email,
+            },
+        );
+        respond(
+            201,
+            &RegisterResponse {
+                statusCode: 201,
+                userId: user_id,
+                userToken: user_token,
+            },
+        )
+    }
+
+    fn get_user_id(&self, user_token: &str) -> Option<String> {
+        self.users.get(user_token).map(|u| u.id.clone())
+    }
+
+    pub fn connect(&mut self, payload: ConnectPayload) -> Response {
+        let user_id = match self.get_user_id(&payload.userToken) {
+            Some(user_id) => user_id,
+            None => return error(401, "Unknown user token"),
+        };
+        if self.options.rival == Rival::Player {
+            let waiting = self
+                .games
+                .iter_mut()
+                .find(|(_, g)| g.circle_id.is_none() && g.cross_id.as_ref() != Some(&user_id));
+            if let Some((game_token, game)) = waiting {
+                game.circle_id = Some(user_id);
+                game.updated_at = now();
+                self.changed = true;
+                let response = ConnectResponse {
+                    statusCode: 201,
+                    gameToken: game_token.clone(),
+                    gameId: game.id.clone(),
+                };
+                return respond(201, &response);
+            }
+        }
+
+        let game_token = self.generate_id("game-token");
+        let (cross_id, circle_id) = match self.options.rival {
+            Rival::Player => (Some(user_id), None),
+            _ if self.options.rival_starts => (Some(String::from(RIVAL_ID)), Some(user_id)),
+            _ => (Some(user_id), Some(String::from(RIVAL_ID))),
+        };
+        let game = Game {
+            id: self.generate_id("game"),
+            cross_id,
+            circle_id,
+            moves: Vec::new(),
+            winner_id: None,
+            updated_at: now(),
+            timed_out: false,
+            script_index: 0,
+        };
+        let response = ConnectResponse {
+            statusCode: 201,
+            gameToken: game_token.clone(),
+            gameId: game.id.clone(),
+        };
+        self.games.insert(game_token.clone(), game);
+        self.changed = true;
+        self.play_rival(&game_token);
+        respond(201, &response)
+    }
+
+    /// Game of the player, or the error response
+    fn get_game(&self, user_token: &str, game_token: &str) -> Result<(String, &Game), Response> {
+        let user_id = self
+            .get_user_id(user_token)
+            .ok_or_else(|| error(401, "Unknown user token"))?;
+        let game = self
+            .games
+            .get(game_token)
+            .ok_or_else(|| error(404, "Unknown game token"))?;
+        if !game.has_player(&user_id) {
+            return Err(error(403, "Not a player of the game"));
+        }
+        Ok((user_id, game))
+    }
+
+    pub fn play(&mut self, payload: PlayPayload) -> Response {
+        let user_id = match self.get_game(&payload.userToken, &payload.gameToken) {
+            Ok((user_id, game)) => {
+                if game.winner_id.is_some() {
+                    return respond(FINISHED, &game.get_status(true).1);
+                }
+                if game.get_actual_player_id() != Some(&user_id) {
+                    return error(409, "Not your turn");
+                }
+                if game
+                    .moves
+                    .iter()
+                    .any(|(_, x, y)| (*x, *y) == (payload.positionX, payload.positionY))
+                {
+                    return error(409, "The position is occupied");
+                }
+                user_id
+            }
+            Err(response) => return response,
+        };
+        let game = self.games.get_mut(&payload.gameToken).unwrap();
+        game.put_move(&user_id, payload.positionX, payload.positionY);
+        self.changed = true;
+        let (status, response) = game.get_status(true);
+        self.play_rival(&payload.gameToken);
+        if self.options.lost_responses > 0 {
+            self.options.lost_responses -= 1;
+            return error(500, "The response got lost");
+        }
+        respond(if status == FINISHED { FINISHED } else { 201 }, &response)
+    }
+
+    pub fn check_status(&mut self, payload: StatusPayload, last_only: bool) -> Response {
+        match self.get_game(&payload.userToken, &payload.gameToken) {
+            Ok((_, game)) => {
+                let (status, response) = game.get_status(last_only);
+                respond(status, &response)
+            }
+            Err(response) => response,
+        }
+    }
+
+    /// Lets the built-in rival play when it is on the move
+    fn play_rival(&mut self, game_token: &str) {
+        let rival = self.options.rival.clone();
+        let game = match self.games.get_mut(game_token) {
+            Some(game) => game,
+            None => return,
+        };
+        if game.get_actual_player_id().map(|id| id.as_str()) != Some(RIVAL_ID) {
+            return;
+        }
+        let (x, y) = match rival {
+            Rival::Player => return,
+            Rival::Scripted(script) => {
+                let scripted = script[game.script_index.min(script.len())..]
+                    .iter()
+                    .position(|(x, y)| !game.moves.iter().any(|m| (m.1, m.2) == (*x, *y)));
+                match scripted {
+                    Some(offset) => {
+                        let index = game.script_index + offset;
+                        game.script_index = index + 1;
+                        script[index]
+                    }
+                    None => get_engine_move(game, 0),
+                }
+            }
+            Rival::Engine(depth) => get_engine_move(game, depth),
+        };
+        game.put_move(RIVAL_ID, x, y);
+    }
+}
+
+fn get_engine_move(game: &Game, depth: u8) -> (i32, i32) {
+    let position = game.get_position(RIVAL_ID);
+    if position.moves.is_empty() {
+        return (0, 0);
+    }
+    let mut game_play = GamePlay::<FiveInRow, NoConnection>::from_game(position);
+    game_play.verbosity = Verbosity::Quiet;
+    game_play.search_depth = depth;
+    game_play
+        .search()
+        .and_then(|_| game_play.suggest_move(true))
+        .map_or((0, 0), |s| (s.get_move().get_x(), s.get_move().get_y()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn register(state: &mut ServerState) -> String {
+        let (_, body) = state.register(RegisterPayload {
+            nickname: format!("bot{}", state.users.len()),
+            email: String::from("bot@example.com"),
+        });
+        String::from(body["userToken"].as_str().unwrap())
+    }
+
+    fn play(state: &mut ServerState, user_token: &str, game_token: &str, x: i32, y: i32) -> u16 {
+        state
+            .play(PlayPayload {
+                userToken: String::from(user_token),
+                gameToken: String::from(game_token),
+                positionX: x,
+                positionY: y,
+            })
+            .0
+    }
+
+    #[test]
+    fn it_plays_against_scripted_rival() {
+        let mut state = ServerState::new(Options {
+            rival: Rival::Scripted(vec![(0, 0), (5, 5), (5, 7), (5, 9), (5, 11), (5, 13)]),
+            ..Options::default()
+        });
+        let user_token = register(&mut state);
+        let (status, body) = state.connect(ConnectPayload {
+            userToken: user_token.clone(),
+        });
+        assert_eq!(status, 201);
+        let game_token = String::from(body["gameToken"].as_str().unwrap());
+
+        assert_eq!(play(&mut state, &user_token, &game_token, 0, 0), 201);
+        // the rival skipped the occupied (0, 0)
+        assert_eq!(state.games[&game_token].moves[1], (RIVAL_ID.into(), 5, 5));
+        assert_eq!(play(&mut state, &user_token, &game_token, 5, 5), 409);
+        assert_eq!(play(&mut state, &user_token, &game_token, 1, 0), 201);
+        for x in 2..4 {
+            assert_eq!(play(&mut state, &user_token, &game_token, x, 0), 201);
+        }
+        assert_eq!(play(&mut state, &user_token, &game_token, 4, 0), FINISHED);
+        let game = &state.games[&game_token];
+        assert_eq!(game.winner_id, game.cross_id);
+        assert_eq!(game.get_actual_player_id(), None);
+        assert_eq!(play(&mut state, &user_token, &game_token, 9, 9), FINISHED);
+        assert_eq!(
+            play(&mut state, "unknown", &game_token, 9, 9),
+            401,
+            "unknown users are rejected"
+        );
+    }
+
+    #[test]
+    fn it_pairs_players() {
+        let mut state = ServerState::new(Options {
+            rival: Rival::Player,
+            ..Options::default()
+        });
+        let (first, second) = (register(&mut state), register(&mut state));
+        let (_, body) = state.connect(ConnectPayload {
+            userToken: first.clone(),
+        });
+        let game_token = String::from(body["gameToken"].as_str().unwrap());
+        let status = |state: &mut ServerState, token: &str| {
+            state.check_status(
+                StatusPayload {
+                    userToken: String::from(token),
+                    gameToken: game_token.clone(),
+                },
+                false,
+            )
+        };
+        assert_eq!(status(&mut state, &first).1["actualPlayerId"], Value::Null);
+        assert_eq!(status(&mut state, &second).0, 403);
+
+        let (_, body) = state.connect(ConnectPayload {
+            userToken: second.clone(),
+        });
+        assert_eq!(body["gameToken"], game_token.as_str());
+        let (_, body) = status(&mut state, &second);
+        assert_eq!(body["actualPlayerId"], body["playerCrossId"]);
+        assert_eq!(play(&mut state, &second, &game_token, 0, 0), 409);
+        assert_eq!(play(&mut state, &first, &game_token, 0, 0), 201);
+        assert_eq!(play(&mut state, &second, &game_token, 1, 0), 201);
+        assert_eq!(
+            status(&mut state, &first).1["coordinates"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn it_limits_request_rate() {
+        let mut state = ServerState::new(Options {
+            min_interval: Some(Duration::from_secs(60)),
+            ..Options::default()
+        });
+        assert!(!state.is_rate_limited("a"));
+        assert!(state.is_rate_limited("a"));
+        assert!(!state.is_rate_limited("b"));
+    }
+
+    fn start_game(state: &mut ServerState) -> (String, String, String) {
+        let (first, second) = (register(state), register(state));
+        let mut game_token = String::new();
+        for token in [&first, &second].iter() {
+            let (_, body) = state.connect(ConnectPayload {
+                userToken: String::from(*token),
+            });
+            game_token = String::from(body["gameToken"].as_str().unwrap());
+        }
+        (first, second, game_token)
+    }
+
+    #[test]
+    fn it_rejects_taken_nickname() {
+        let mut state = ServerState::new(Options::default());
+        register(&mut state);
+        let (status, _) = state.register(RegisterPayload {
+            nickname: String::from("bot0"),
+            email: String::from("other@example.com"),
+        });
+        assert_eq!(status, 409);
+    }
+
+    #[test]
+    fn it_ends_games_on_timeout() {
+        let mut state = ServerState::new(Options {
+            rival: Rival::Player,
+            turn_timeout: Some(Duration::from_secs(10)),
+            ..Options::default()
+        });
+        let (first, _, game_token) = start_game(&mut state);
+        assert_eq!(play(&mut state, &first, &game_token, 0, 0), 201);
+        let updated_at = state.games[&game_token].updated_at;
+        state.expire_games(updated_at + 10_000);
+        assert_eq!(state.games[&game_token].winner_id, None);
+        state.expire_games(updated_at + 10_001);
+        let game = &state.games[&game_token];
+        assert!(game.timed_out);
+        assert_eq!(game.winner_id, game.cross_id);
+
+        let leaderboard = state.get_leaderboard();
+        assert_eq!(leaderboard.len(), 2);
+        assert_eq!(leaderboard[0].userId, *game.cross_id.as_ref().unwrap());
+        assert_eq!((leaderboard[0].wins, leaderboard[0].losses), (1, 0));
+        assert_eq!((leaderboard[1].wins, leaderboard[1].losses), (0, 1));
+    }
+
+    #[test]
+    fn it_persists_users_and_games() {
+        let path = std::env::temp_dir().join("five_in_a_row_it_persists_users_and_games.json");
+        let _ = fs::remove_file(&path);
+        let options = Options {
+            rival: Rival::Player,
+            ..Options::default()
+        };
+        let mut state = ServerState::load(&path, options.clone()).unwrap();
+        let (first, _, game_token) = start_game(&mut state);
+        assert_eq!(play(&mut state, &first, &game_token, 0, 0), 201);
+        state.save().unwrap();
+
+        let mut loaded = ServerState::load(&path, options).unwrap();
+        assert_eq!(loaded.users.len(), 2);
+        assert_eq!(loaded.games[&game_token].moves.len(), 1);
+        assert_eq!(loaded.next_id, state.next_id);
+        assert_eq!(play(&mut loaded, &first, &game_token, 1, 0), 409);
+        fs::remove_file(&path).unwrap();
+    }
+}
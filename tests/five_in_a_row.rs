@@ -8,6 +8,7 @@ mod tests {
         gameplay::GamePlay,
     };
     use std::collections::VecDeque;
+    use std::time::Duration;
 
     pub struct MockConnection {}
     #[async_trait]
@@ -37,7 +38,7 @@ mod tests {
             FiveInRowMove::Rivals(0, 3),
         ]);
         let game = FiveInRow::from_moves(moves);
-        game.visualize();
+        println!("{}", game.visualize());
         let mut game_play = GamePlay::<FiveInRow, MockConnection>::from_game(game);
         let suggested = game_play.suggest_move(true).unwrap();
         assert_eq!(*suggested.get_move(), FiveInRowMove::Mine(0, 4));
@@ -54,7 +55,7 @@ mod tests {
             FiveInRowMove::Rivals(0, 3),
         ]);
         let game = FiveInRow::from_moves(moves);
-        game.visualize();
+        println!("{}", game.visualize());
         let mut game_play = GamePlay::<FiveInRow, MockConnection>::from_game(game);
         game_play
             .compute_suggestions(true, VecDeque::new(), 0)
@@ -78,7 +79,7 @@ mod tests {
             FiveInRowMove::Rivals(2, 0),
         ]);
         let game = FiveInRow::from_moves(moves);
-        game.visualize();
+        println!("{}", game.visualize());
         let mut game_play = GamePlay::<FiveInRow, MockConnection>::from_game(game);
         game_play
             .compute_suggestions(true, VecDeque::new(), 1)
@@ -119,7 +120,7 @@ mod tests {
             FiveInRowMove::Rivals(0, 1),
         ]);
         let game = FiveInRow::from_moves(moves);
-        game.visualize();
+        println!("{}", game.visualize());
         let mut game_play = GamePlay::<FiveInRow, MockConnection>::from_game(game);
         game_play
             .compute_suggestions(true, VecDeque::new(), 2)
@@ -153,7 +154,7 @@ mod tests {
             FiveInRowMove::Rivals(-2, 1),
         ]);
         let game = FiveInRow::from_moves(moves);
-        game.visualize();
+        println!("{}", game.visualize());
         let mut game_play = GamePlay::<FiveInRow, MockConnection>::from_game(game);
         game_play
             .compute_suggestions(true, VecDeque::new(), 3)
@@ -186,7 +187,7 @@ mod tests {
             FiveInRowMove::Rivals(2, 1),
         ]);
         let game = FiveInRow::from_moves(moves);
-        game.visualize();
+        println!("{}", game.visualize());
         let mut game_play = GamePlay::<FiveInRow, MockConnection>::from_game(game);
         game_play
             .compute_suggestions(true, VecDeque::new(), 6)
@@ -204,58 +205,26 @@ mod tests {
         );
     }
 
-    // #[test]
-    fn it_suggests_correct_move_7() {
-        // https://piskvorky.jobs.cz/detail-hry/9829163b-c578-4b0b-a334-baab9863c76f/
+    #[test]
+    fn it_limits_search_by_move_time() {
         let moves = Vec::from([
-            FiveInRowMove::Mine(0, 0),
-            FiveInRowMove::Rivals(-1, 0),
-            FiveInRowMove::Mine(-4, 0),
-            FiveInRowMove::Rivals(-2, 1),
+            FiveInRowMove::Rivals(0, 0),
             FiveInRowMove::Mine(0, -1),
             FiveInRowMove::Rivals(0, 1),
             FiveInRowMove::Mine(0, -2),
-            FiveInRowMove::Rivals(-1, 2),
+            FiveInRowMove::Rivals(0, 2),
             FiveInRowMove::Mine(0, -3),
-            FiveInRowMove::Rivals(0, -4),
-            FiveInRowMove::Mine(-1, 1),
-            FiveInRowMove::Rivals(-2, 3),
-            FiveInRowMove::Mine(1, 0),
-            FiveInRowMove::Rivals(-3, 2),
-            FiveInRowMove::Mine(-2, 2),
-            FiveInRowMove::Rivals(1, -1),
-            FiveInRowMove::Mine(-3, 3),
-            FiveInRowMove::Rivals(-4, 4),
-            FiveInRowMove::Mine(-3, 4),
-            FiveInRowMove::Rivals(-2, -1),
-            FiveInRowMove::Mine(-3, -2),
-            FiveInRowMove::Rivals(-3, 0),
-            FiveInRowMove::Mine(-4, -1),
-            FiveInRowMove::Rivals(-4, 1),
-            FiveInRowMove::Mine(-1, -2),
-            FiveInRowMove::Rivals(-5, 0),
-            FiveInRowMove::Mine(-1, 4),
-            FiveInRowMove::Rivals(-6, -1),
+            FiveInRowMove::Rivals(0, 3),
         ]);
-        let game = FiveInRow::from_moves(moves);
-        game.visualize();
-        let mut game_play = GamePlay::<FiveInRow, MockConnection>::from_game(game);
-        game_play
-            .compute_suggestions(true, VecDeque::new(), 2)
-            .unwrap();
+        let mut game_play =
+            GamePlay::<FiveInRow, MockConnection>::from_game(FiveInRow::from_moves(moves));
+        game_play.move_time = Some(Duration::from_nanos(1));
+        assert_eq!(game_play.search().unwrap(), 0);
         let suggested = game_play.suggest_move(true).unwrap();
-        let suggested_move = *suggested.get_move();
+        assert_eq!(*suggested.get_move(), FiveInRowMove::Mine(0, 4));
 
-        // TODO inspect game and find the place where the algorithm failed
-        /*
-        assert!(
-            suggested_move == FiveInRowMove::Mine(2, -2)
-                || suggested_move == FiveInRowMove::Mine(2, 2)
-                || suggested_move == FiveInRowMove::Mine(-2, -3)
-                || suggested_move == FiveInRowMove::Mine(-3, -4),
-            "Expected (2, -2), (2, 2), (-2, -3) or (-3, -4) got {:?}",
-            suggested_move
-        );
-        */
+        game_play.search_depth = 1;
+        game_play.move_time = Some(Duration::from_secs(60));
+        assert_eq!(game_play.search().unwrap(), 1);
     }
 }
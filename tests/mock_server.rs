@@ -0,0 +1,457 @@
+#[cfg(test)]
+mod tests {
+    use game_play::{
+        api::{
+            game_connection::GameConnection,
+            jobs_cz::{fetch, play, register, status, JobsApi},
+            rate_limiter::RateLimiter,
+        },
+        five_in_a_row::{mv::FiveInRowMove, FiveInRow},
+        game::{error::Error, GameMove},
+        gameplay::{GamePlay, Verbosity},
+        scheduler::{GameSettings, Scheduler},
+        server::{
+            state::{Options, Rival, RIVAL_ID},
+            MockServer,
+        },
+    };
+    use std::time::Duration;
+
+    fn client(server: &MockServer) -> fetch::JobsApi {
+        fetch::JobsApi::from_settings(&fetch::Settings {
+            base_url: server.get_base_url(),
+            ..fetch::Settings::default()
+        })
+        .unwrap()
+        .with_limiter(RateLimiter::new(1, Duration::from_millis(2)))
+    }
+
+    async fn register(server: &MockServer, nickname: &str) -> register::RegisterResponse {
+        register::invoke_registration(
+            &mut client(server),
+            &register::RegisterPayload {
+                nickname: String::from(nickname),
+                email: format!("{}@example.com", nickname),
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    async fn connect(server: &MockServer, nickname: &str) -> (String, JobsApi) {
+        let user = register(server, nickname).await;
+        let api = JobsApi::new(&user.userId, &user.userToken).with_client(client(server));
+        (user.userId, api)
+    }
+
+    async fn play_game(api: JobsApi, depth: u8) -> GamePlay<FiveInRow, JobsApi> {
+        let mut game_play = GamePlay::from_api(api).await.unwrap();
+        game_play.verbosity = Verbosity::Quiet;
+        game_play.search_depth = depth;
+        game_play.play().await.unwrap();
+        game_play
+    }
+
+    #[tokio::test]
+    async fn it_wins_against_scripted_rival() {
+        let server = MockServer::start(Options {
+            rival: Rival::Scripted((0..20).map(|y| (20, y * 2)).collect()),
+            ..Options::default()
+        })
+        .await
+        .unwrap();
+        let (user_id, api) = connect(&server, "bot").await;
+        let game_play = play_game(api, 1).await;
+
+        let record = game_play.record.unwrap();
+        assert_eq!(record.winner_id.as_ref(), Some(&user_id));
+        assert_eq!(record.info.player_cross_id.as_ref(), Some(&user_id));
+        assert_eq!(record.moves.len() % 2, 1);
+        let state = server.state.lock().unwrap();
+        let game = state.games.values().next().unwrap();
+        assert_eq!(game.moves.len(), record.moves.len());
+        assert!(game.get_position(&user_id).get_winning_line().is_some());
+    }
+
+    #[tokio::test]
+    async fn it_plays_full_game_against_engine() {
+        let server = MockServer::start(Options {
+            rival: Rival::Engine(1),
+            rival_starts: true,
+            // the client sends requests faster and has to back off
+            min_interval: Some(Duration::from_millis(10)),
+            ..Options::default()
+        })
+        .await
+        .unwrap();
+        let (user_id, api) = connect(&server, "bot").await;
+        let game_play = play_game(api, 1).await;
+
+        let record = game_play.record.unwrap();
+        let winner_id = record.winner_id.clone().unwrap();
+        assert!(winner_id == user_id || winner_id == RIVAL_ID);
+        assert_eq!(record.info.player_circle_id.as_ref(), Some(&user_id));
+        let state = server.state.lock().unwrap();
+        let game = state.games.values().next().unwrap();
+        assert_eq!(game.winner_id.as_ref(), Some(&winner_id));
+        // the opening move of the rival is recorded too
+        assert_eq!(record.get_moves(), game_play.game.moves);
+        assert!(!record.moves[0].mv.is_mine());
+        // the winning move is recorded whoever played it
+        assert_eq!(game.moves.len(), game_play.game.moves.len());
+        assert_eq!(record.moves.len(), game.moves.len());
+        assert_eq!(game_play.game.moves, game.get_position(&user_id).moves);
+    }
+
+    #[tokio::test]
+    async fn it_returns_winning_move_of_rival() {
+        let server = MockServer::start(Options {
+            rival: Rival::Scripted((0..5).map(|y| (20, y)).collect()),
+            ..Options::default()
+        })
+        .await
+        .unwrap();
+        let (_, mut api) = connect(&server, "bot").await;
+        api.start_game().await.unwrap();
+        for (x, y) in [(0, 0), (0, 3), (3, 0), (3, 3)] {
+            api.put_move(&FiveInRowMove::Mine(x, y)).await.unwrap();
+            let (rivals_move, winner) = api.await_move().await.unwrap();
+            assert!(rivals_move.is_some());
+            assert_eq!(winner, None);
+        }
+        api.put_move(&FiveInRowMove::Mine(6, 6)).await.unwrap();
+        assert_eq!(
+            api.await_move().await.unwrap(),
+            (
+                Some(FiveInRowMove::Rivals(20, 4)),
+                Some(String::from(RIVAL_ID))
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn it_accepts_move_with_lost_response() {
+        let server = MockServer::start(Options {
+            rival: Rival::Scripted((0..20).map(|y| (20, y * 2)).collect()),
+            lost_responses: 1,
+            ..Options::default()
+        })
+        .await
+        .unwrap();
+        let (user_id, mut api) = connect(&server, "bot").await;
+        api.start_game().await.unwrap();
+        api.put_move(&FiveInRowMove::Mine(0, 0)).await.unwrap();
+        assert_eq!(
+            api.await_move().await.unwrap(),
+            (Some(FiveInRowMove::Rivals(20, 0)), None)
+        );
+        let state = server.state.lock().unwrap();
+        let game = state.games.values().next().unwrap();
+        assert_eq!(
+            game.get_position(&user_id).moves,
+            vec![FiveInRowMove::Mine(0, 0), FiveInRowMove::Rivals(20, 0)]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_replays_recorded_game_offline() {
+        let server = MockServer::start(Options {
+            rival: Rival::Engine(1),
+            min_interval: Some(Duration::from_millis(10)),
+            ..Options::default()
+        })
+        .await
+        .unwrap();
+        let path = std::env::temp_dir().join(format!(
+            "five_in_a_row_replayed_game_{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let user = register(&server, "recorded").await;
+        let recorded = JobsApi::new(&user.userId, &user.userToken)
+            .with_client(client(&server).record_to(&path).unwrap());
+        let recorded = play_game(recorded, 1).await;
+        drop(server);
+
+        let replayed = JobsApi::new(&user.userId, "unknown")
+            .with_client(fetch::JobsApi::from_recording(&path).unwrap());
+        let replayed = play_game(replayed, 1).await;
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(replayed.game.moves, recorded.game.moves);
+        assert_eq!(
+            replayed.record.unwrap().winner_id,
+            recorded.record.unwrap().winner_id
+        );
+    }
+
+    #[tokio::test]
+    async fn it_resumes_game_after_restart() {
+        let server = MockServer::start(Options {
+            rival: Rival::Scripted((0..20).map(|y| (20, y * 2)).collect()),
+            ..Options::default()
+        })
+        .await
+        .unwrap();
+        let game_file = std::env::temp_dir().join(format!(
+            "five_in_a_row_resumed_game_{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&game_file);
+        let (user_id, api) = connect(&server, "bot").await;
+        let api = api.with_game_file(&game_file);
+
+        // the process stops after the first move
+        let mut crashed = api.clone();
+        crashed.start_game().await.unwrap();
+        crashed.put_move(&FiveInRowMove::Mine(0, 0)).await.unwrap();
+        let game_token = crashed.get_game_info().game_token;
+        assert!(game_file.exists());
+
+        let mut resumed = GamePlay::from_api(api.clone()).await.unwrap();
+        assert_eq!(
+            resumed
+                .connection
+                .as_ref()
+                .unwrap()
+                .get_game_info()
+                .game_token,
+            game_token
+        );
+        assert_eq!(
+            resumed.game.moves,
+            vec![FiveInRowMove::Mine(0, 0), FiveInRowMove::Rivals(20, 0)]
+        );
+        resumed.verbosity = Verbosity::Quiet;
+        resumed.search_depth = 1;
+        assert_eq!(resumed.play().await.unwrap(), user_id);
+        assert!(!game_file.exists());
+        assert_eq!(server.state.lock().unwrap().games.len(), 1);
+        // the moves played before the restart are recorded too
+        let record = resumed.record.unwrap();
+        assert_eq!(record.get_moves(), resumed.game.moves);
+        assert_eq!(
+            record.get_moves()[..2],
+            [FiveInRowMove::Mine(0, 0), FiveInRowMove::Rivals(20, 0)]
+        );
+
+        // the next game is a new one
+        let next = GamePlay::<FiveInRow, JobsApi>::from_api(api).await.unwrap();
+        assert_ne!(
+            next.connection.unwrap().get_game_info().game_token,
+            game_token
+        );
+        assert!(next.game.moves.len() <= 1);
+        std::fs::remove_file(&game_file).unwrap();
+    }
+
+    fn scripted_server() -> Options {
+        Options {
+            rival: Rival::Scripted((0..20).map(|y| (20, y * 2)).collect()),
+            ..Options::default()
+        }
+    }
+
+    /// Adds moves to the server unnoticed by the client
+    fn tamper(server: &MockServer, moves: &[(&str, i32, i32)]) {
+        let mut state = server.state.lock().unwrap();
+        let game = state.games.values_mut().next().unwrap();
+        for (player_id, x, y) in moves {
+            game.moves.push((String::from(*player_id), *x, *y));
+        }
+    }
+
+    #[tokio::test]
+    async fn it_detects_desync() {
+        let server = MockServer::start(scripted_server()).await.unwrap();
+        let (user_id, api) = connect(&server, "bot").await;
+        let mut api = api.with_verify_interval(None);
+        let mut game = api.start_game().await.unwrap();
+        api.put_move(&FiveInRowMove::Mine(0, 0)).await.unwrap();
+        assert_eq!(
+            api.await_move().await.unwrap(),
+            (Some(FiveInRowMove::Rivals(20, 0)), None)
+        );
+        game.moves
+            .extend([FiveInRowMove::Mine(0, 0), FiveInRowMove::Rivals(20, 0)]);
+
+        // our move got lost, the rival's move can't follow the rival's one
+        tamper(&server, &[(&user_id, 5, 5), (RIVAL_ID, 20, 2)]);
+        let err = api.await_move().await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Desync {
+                local: 2,
+                server: 4
+            }
+        ));
+        let game = api.get_server_game(&game).await.unwrap().unwrap();
+        assert_eq!(
+            game.moves[2..],
+            [FiveInRowMove::Mine(5, 5), FiveInRowMove::Rivals(20, 2)]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_reloads_board_after_desync() {
+        let server = MockServer::start(scripted_server()).await.unwrap();
+        let (user_id, api) = connect(&server, "bot").await;
+        let mut game_play = GamePlay::from_api(api.with_verify_interval(Some(1)))
+            .await
+            .unwrap();
+        game_play.verbosity = Verbosity::Quiet;
+        game_play.search_depth = 1;
+        let connection = game_play.connection.as_mut().unwrap();
+        connection
+            .put_move(&FiveInRowMove::Mine(0, 0))
+            .await
+            .unwrap();
+        game_play.add_move(FiveInRowMove::Mine(0, 0)).unwrap();
+
+        // a status with the rival's move and our next move is missed
+        tamper(&server, &[(&user_id, 5, 5), (RIVAL_ID, 20, 2)]);
+        assert_eq!(game_play.play().await.unwrap(), user_id);
+        let state = server.state.lock().unwrap();
+        let game = state.games.values().next().unwrap();
+        assert_eq!(game_play.game.moves.len(), game.moves.len());
+        assert_eq!(
+            game_play.game.moves[..4],
+            game.get_position(&user_id).moves[..4]
+        );
+        // the record follows the reloaded board
+        assert_eq!(game_play.record.unwrap().get_moves(), game_play.game.moves);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn it_schedules_concurrent_games() {
+        let server = MockServer::start(Options {
+            rival: Rival::Engine(0),
+            ..Options::default()
+        })
+        .await
+        .unwrap();
+        let logs_dir = std::env::temp_dir().join(format!(
+            "five_in_a_row_scheduled_logs_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&logs_dir);
+        // all the slots share the rate limit of one client
+        let client = client(&server);
+        let mut slots = Vec::new();
+        for nickname in ["first", "second"] {
+            let user = register(&server, nickname).await;
+            for _ in 0..2 {
+                slots.push(JobsApi::new(&user.userId, &user.userToken).with_client(client.clone()));
+            }
+        }
+        let records_dir = logs_dir.join("records");
+        let settings = GameSettings {
+            search_depth: 1,
+            verbosity: Verbosity::Quiet,
+            logs_dir: Some(logs_dir.clone()),
+            records_dir: Some(records_dir.clone()),
+            diagrams: true,
+            ..GameSettings::default()
+        };
+        let summary = Scheduler::new(slots, settings)
+            .with_games(Some(5))
+            .with_cpu_budget(1)
+            .run()
+            .await;
+
+        assert_eq!(summary.accounts.len(), 2);
+        let total = summary.get_total();
+        assert_eq!(total.games, 5);
+        assert_eq!(total.errors, 0);
+        assert_eq!(server.state.lock().unwrap().games.len(), 5);
+        let files = |extension: &str| {
+            std::fs::read_dir(&records_dir)
+                .unwrap()
+                .filter(|e| e.as_ref().unwrap().path().extension().unwrap() == extension)
+                .count()
+        };
+        assert_eq!(files("json"), 5);
+        assert_eq!(files("svg"), 5);
+        let logs = std::fs::read_dir(&logs_dir)
+            .unwrap()
+            .filter(|e| e.as_ref().unwrap().path().is_file())
+            .count();
+        assert_eq!(logs, 5);
+        std::fs::remove_dir_all(&logs_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_plays_two_clients_against_each_other() {
+        let server = MockServer::start(Options {
+            rival: Rival::Player,
+            ..Options::default()
+        })
+        .await
+        .unwrap();
+        let (first_id, first) = connect(&server, "first").await;
+        let (second_id, second) = connect(&server, "second").await;
+        let (first, second) = tokio::join!(play_game(first, 1), play_game(second, 0));
+
+        let winner_id = first.record.unwrap().winner_id.unwrap();
+        assert_eq!(second.record.unwrap().winner_id, Some(winner_id.clone()));
+        assert!(winner_id == first_id || winner_id == second_id);
+    }
+
+    #[tokio::test]
+    async fn it_rejects_invalid_moves() {
+        let server = MockServer::start(Options {
+            rival: Rival::Scripted(vec![(0, 0)]),
+            ..Options::default()
+        })
+        .await
+        .unwrap();
+        let (_, mut api) = connect(&server, "bot").await;
+        api.start_game().await.unwrap();
+        let info = api.get_game_info();
+        let user = server
+            .state
+            .lock()
+            .unwrap()
+            .users
+            .keys()
+            .next()
+            .cloned()
+            .unwrap();
+        let mut client = client(&server);
+        let payload = |x, y| play::PlayPayload {
+            userToken: user.clone(),
+            gameToken: info.game_token.clone().unwrap(),
+            positionX: x,
+            positionY: y,
+        };
+        play::invoke_move(&mut client, &payload(0, 0))
+            .await
+            .unwrap();
+        let status_payload = status::StatusPayload {
+            userToken: user.clone(),
+            gameToken: info.game_token.clone().unwrap(),
+        };
+        let status = status::fetch_status(&mut client, &status_payload)
+            .await
+            .unwrap();
+        assert_eq!(status.coordinates.len(), 2);
+        assert_eq!(status.actualPlayerId, info.user_id);
+
+        let rivals = &status.coordinates[1];
+        let err = play::invoke_move(&mut client, &payload(rivals.x, rivals.y))
+            .await
+            .unwrap_err();
+        assert!(!err.is_retryable());
+        assert!(err.to_string().contains("409"));
+
+        let err = status::fetch_status(
+            &mut client,
+            &status::StatusPayload {
+                userToken: String::from("unknown"),
+                ..status_payload
+            },
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("401"));
+    }
+}
@@ -0,0 +1,26 @@
+#[cfg(test)]
+mod tests {
+    use game_play::analysis::puzzles;
+    use std::path::Path;
+
+    #[test]
+    fn it_solves_puzzles() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/puzzles.txt");
+        let results = puzzles::load(&path)
+            .unwrap()
+            .iter()
+            .map(|puzzle| puzzles::solve(puzzle).unwrap())
+            .collect::<Vec<_>>();
+        println!("{}", puzzles::format_report(&results));
+        let regressions = results
+            .iter()
+            .filter(|r| r.is_regression())
+            .map(|r| r.name.as_str())
+            .collect::<Vec<_>>();
+        assert!(
+            regressions.is_empty(),
+            "Unsolved puzzles: {:?}",
+            regressions
+        );
+    }
+}